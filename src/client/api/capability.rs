@@ -0,0 +1,307 @@
+use crate::client::api::RingApi;
+use crate::client::api::error::ApiError;
+use crate::device::Device;
+use crate::helper::url::Url;
+use bytes::Bytes;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::future::Future;
+use tokio::io::AsyncWrite;
+
+mod sealed {
+    /// Stops the capability traits from being implemented outside of the crate, so that
+    /// methods can be added to them without breaking anyone.
+    pub trait Sealed {}
+}
+
+/// A device with a camera (such as a doorbell, or a Floodlight Cam).
+///
+/// See [`Device::as_camera`].
+///
+/// The capability traits are sealed (they can only be implemented by the crate), and their
+/// methods return futures, so they're used through the `impl Trait` returned by [`Device`]
+/// rather than as trait objects.
+pub trait Camera: sealed::Sealed {
+    /// Get the latest snapshot captured by the camera.
+    ///
+    /// See [`Device::snapshot`].
+    fn snapshot(&self) -> impl Future<Output = Result<Bytes, ApiError>> + Send;
+
+    /// Capture a new snapshot from the camera, and wait for it to be available.
+    ///
+    /// See [`Device::request_fresh_snapshot`].
+    fn request_fresh_snapshot(&self) -> impl Future<Output = Result<Bytes, ApiError>> + Send;
+
+    /// Download the recording of an item in the history of the camera.
+    ///
+    /// See [`Device::download_recording`].
+    fn download_recording(
+        &self,
+        ding_id: u64,
+        writer: impl AsyncWrite + Unpin + Send,
+    ) -> impl Future<Output = Result<u64, ApiError>> + Send;
+}
+
+/// A device with a light (such as a Floodlight Cam, or a Spotlight Cam).
+///
+/// See [`Device::as_light`]. Like [`Camera`], this trait is sealed.
+pub trait Light: sealed::Sealed {
+    /// Whether the light was on when the device was retrieved, if Ring said.
+    fn is_light_on(&self) -> Option<bool>;
+
+    /// Turn the light on or off.
+    fn set_light(&self, on: bool) -> impl Future<Output = Result<(), ApiError>> + Send;
+}
+
+/// A device with a siren (such as a Stick Up Cam, or a Floodlight Cam).
+///
+/// See [`Device::as_siren`]. Like [`Camera`], this trait is sealed.
+pub trait Siren: sealed::Sealed {
+    /// Whether the siren was sounding when the device was retrieved, if Ring said.
+    fn is_siren_on(&self) -> Option<bool>;
+
+    /// Sound or silence the siren.
+    fn set_siren(&self, on: bool) -> impl Future<Output = Result<(), ApiError>> + Send;
+}
+
+/// The sounds a [`Chime`] can play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ChimeSound {
+    /// The sound played when a doorbell is pressed.
+    Ding,
+
+    /// The sound played when motion is detected.
+    Motion,
+}
+
+/// A device which plays a sound when a doorbell is pressed (such as a Chime Pro).
+///
+/// See [`Device::as_chime`]. Like [`Camera`], this trait is sealed.
+pub trait Chime: sealed::Sealed {
+    /// The volume of the chime (from 0 to 10), if Ring said.
+    fn volume(&self) -> Option<u8>;
+
+    /// Play one of the sounds of the chime (i.e. to test it).
+    fn play_sound(&self, sound: ChimeSound) -> impl Future<Output = Result<(), ApiError>> + Send;
+}
+
+/// A device which runs on a battery.
+///
+/// See [`Device::as_battery_powered`]. Like [`Camera`], this trait is sealed.
+pub trait BatteryPowered: sealed::Sealed {
+    /// The remaining battery, as a percentage.
+    fn battery_level(&self) -> u8;
+}
+
+/// A view of a device through one of its capabilities.
+struct Capable<'a> {
+    device: &'a Device,
+    device_id: u64,
+}
+
+impl sealed::Sealed for Capable<'_> {}
+
+impl Camera for Capable<'_> {
+    fn snapshot(&self) -> impl Future<Output = Result<Bytes, ApiError>> + Send {
+        self.device.snapshot()
+    }
+
+    fn request_fresh_snapshot(&self) -> impl Future<Output = Result<Bytes, ApiError>> + Send {
+        self.device.request_fresh_snapshot()
+    }
+
+    fn download_recording(
+        &self,
+        ding_id: u64,
+        writer: impl AsyncWrite + Unpin + Send,
+    ) -> impl Future<Output = Result<u64, ApiError>> + Send {
+        self.device.download_recording(ding_id, writer)
+    }
+}
+
+impl Light for Capable<'_> {
+    fn is_light_on(&self) -> Option<bool> {
+        match self.device.data.field("led_status")?.as_str()? {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn set_light(&self, on: bool) -> impl Future<Output = Result<(), ApiError>> + Send {
+        self.device.session.inner.api.set_light(self.device_id, on)
+    }
+}
+
+impl Siren for Capable<'_> {
+    fn is_siren_on(&self) -> Option<bool> {
+        self.device.data.field("siren_status")?["seconds_remaining"]
+            .as_u64()
+            .map(|seconds| seconds > 0)
+    }
+
+    fn set_siren(&self, on: bool) -> impl Future<Output = Result<(), ApiError>> + Send {
+        self.device.session.inner.api.set_siren(self.device_id, on)
+    }
+}
+
+impl Chime for Capable<'_> {
+    fn volume(&self) -> Option<u8> {
+        self.device
+            .data
+            .setting("volume")?
+            .as_u64()
+            .and_then(|volume| u8::try_from(volume).ok())
+    }
+
+    fn play_sound(&self, sound: ChimeSound) -> impl Future<Output = Result<(), ApiError>> + Send {
+        self.device
+            .session
+            .inner
+            .api
+            .play_chime_sound(self.device_id, sound)
+    }
+}
+
+impl BatteryPowered for Capable<'_> {
+    fn battery_level(&self) -> u8 {
+        self.device.battery_level().unwrap_or_default()
+    }
+}
+
+impl Device {
+    /// Use the device as a camera, if it has one.
+    ///
+    /// A device has a camera if Ring sends the features or settings of its recordings and
+    /// motion detection for it.
+    #[must_use]
+    pub fn as_camera(&self) -> Option<impl Camera + '_> {
+        self.has_camera().then(|| self.capable()).flatten()
+    }
+
+    /// Use the light of the device, if it has one.
+    ///
+    /// A camera has a light if Ring sends floodlight settings for it, so (for example) a
+    /// Floodlight Cam and a Spotlight Cam can be used alike.
+    ///
+    /// Smart Lighting (such as Beams) is controlled through the Ring Bridge rather than the
+    /// camera API, and so isn't used as a light.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::capability::Light;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let devices = client.get_devices()
+    ///      .await
+    ///      .expect("Getting devices should not fail");
+    ///
+    /// for light in devices.iter().filter_map(|device| device.as_light()) {
+    ///     light.set_light(true)
+    ///         .await
+    ///         .expect("Turning on the light should not fail");
+    /// }
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn as_light(&self) -> Option<impl Light + '_> {
+        (self.has_camera() && self.data.setting("floodlight_settings").is_some())
+            .then(|| self.capable())
+            .flatten()
+    }
+
+    /// Use the siren of the device, if it has one.
+    ///
+    /// A camera has a siren if Ring sends siren settings for it.
+    #[must_use]
+    pub fn as_siren(&self) -> Option<impl Siren + '_> {
+        (self.has_camera() && self.data.setting("siren_settings").is_some())
+            .then(|| self.capable())
+            .flatten()
+    }
+
+    /// Use the device as a chime, if it is one.
+    ///
+    /// A device is a chime if Ring sends the settings of the sounds it plays for dings.
+    #[must_use]
+    pub fn as_chime(&self) -> Option<impl Chime + '_> {
+        self.data
+            .setting("ding_audio_id")
+            .is_some()
+            .then(|| self.capable())
+            .flatten()
+    }
+
+    /// Use the battery of the device, if it runs on one.
+    ///
+    /// A device runs on a battery if Ring reports its battery level, unless its power mode
+    /// setting says it is wired (i.e. the battery is only a backup).
+    #[must_use]
+    pub fn as_battery_powered(&self) -> Option<impl BatteryPowered + '_> {
+        let wired = self
+            .data
+            .setting("power_mode")
+            .and_then(Value::as_str)
+            .is_some_and(|mode| mode == "wired");
+
+        (self.battery_level().is_some() && !wired)
+            .then(|| self.capable())
+            .flatten()
+    }
+
+    fn has_camera(&self) -> bool {
+        self.data.feature("show_recordings").is_some()
+            || self.data.setting("motion_detection_enabled").is_some()
+    }
+
+    fn capable(&self) -> Option<Capable<'_>> {
+        Some(Capable {
+            device: self,
+            device_id: self.id()?,
+        })
+    }
+}
+
+impl RingApi {
+    pub(crate) async fn set_light(&self, device_id: u64, on: bool) -> Result<(), ApiError> {
+        self.send(|client| client.put(self.endpoints.get_base_url(&Url::Light { device_id, on })))
+            .await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn set_siren(&self, device_id: u64, on: bool) -> Result<(), ApiError> {
+        self.send(|client| client.put(self.endpoints.get_base_url(&Url::Siren { device_id, on })))
+            .await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn play_chime_sound(
+        &self,
+        device_id: u64,
+        sound: ChimeSound,
+    ) -> Result<(), ApiError> {
+        self.send(|client| {
+            client
+                .post(self.endpoints.get_base_url(&Url::ChimeSound { device_id }))
+                .json(&json!({ "kind": sound }))
+        })
+        .await?;
+
+        Ok(())
+    }
+}
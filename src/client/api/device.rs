@@ -1,130 +1,720 @@
-use crate::client::api::RingApi;
+use crate::Client;
 use crate::client::api::error::ApiError;
-use crate::client::authentication::Tokens;
-use crate::helper;
+use crate::client::api::{Listing, RingApi};
 use crate::helper::url::Url;
-use serde::Deserialize;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 
 /// Data about a device in a Ring account.
-#[derive(Deserialize, Debug)]
-#[serde(tag = "kind")]
-#[serde(rename_all = "snake_case")]
+///
+/// Some variants cover a range of models (i.e. every generation of [`DeviceData::Chime`]).
+#[derive(Debug)]
 pub enum DeviceData {
     /// A Ring Security Camera.
     ///
     /// For example, a [Stick Up Camera](https://en-uk.ring.com/products/stick-up-security-camera-plugin).
     CocoaCamera {
-        /// The ID of the device.
-        id: usize,
-
-        /// The ID of the location the device is in.
-        ///
-        /// See [`crate::location::Location`]
-        location_id: String,
-
-        /// The description of the device.
-        description: String,
-
-        #[serde(flatten)]
-        #[allow(missing_docs)]
-        extra: HashMap<String, Value>,
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
     },
+
     /// A Ring Doorbell.
     ///
     /// For example, a [Video Doorbell Pro 2](https://en-uk.ring.com/products/video-doorbell-pro-2).
     DoorbellGrahamCracker {
-        /// The ID of the device.
-        id: usize,
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
 
-        /// The ID of the location the device is in.
-        location_id: String,
+    /// A Ring Doorbell, other than the Video Doorbell Pro 2.
+    ///
+    /// For example, a (battery powered) [Video Doorbell 4](https://en-uk.ring.com/products/video-doorbell-4),
+    /// a wired Video Doorbell Pro or Elite, or a Peephole Cam.
+    Doorbell {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
 
-        /// The description of the device.
-        description: String,
+    /// An earlier generation of Ring Stick Up Camera, or an Indoor Camera.
+    StickUpCam {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A Ring Floodlight Camera.
+    ///
+    /// For example, a [Floodlight Cam Wired Plus](https://en-uk.ring.com/products/floodlight-cam-wired-plus).
+    FloodlightCam {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
 
-        #[serde(flatten)]
-        #[allow(missing_docs)]
-        extra: HashMap<String, Value>,
+    /// A Ring Spotlight Camera.
+    ///
+    /// For example, a [Spotlight Cam Plus](https://en-uk.ring.com/products/spotlight-cam-plus-battery).
+    SpotlightCam {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A Ring Chime, which plays a sound when a doorbell is pressed.
+    Chime {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A Ring Chime Pro, which also extends the Wi-Fi network to nearby devices.
+    ChimePro {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
     },
+
+    /// A Ring Intercom, which is fitted to the handset of an apartment intercom.
+    Intercom {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
     /// A Ring Alarm Base Station.
     ///
     /// They are available as part of the [Alarm Pack](https://en-uk.ring.com/products/alarm-security-5-piece-kit-gen-2).
     BaseStationV1 {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A Ring Smart Lighting Bridge, which connects Smart Lighting to the Ring account.
+    BeamsBridge {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A Ring Smart Lighting device (such as a pathlight, or a motion sensor), which is connected
+    /// using a [`DeviceData::BeamsBridge`].
+    Beam {
+        /// The fields Ring sends for every device.
+        fields: DeviceFields,
+    },
+
+    /// A device which is yet to be mapped by the crate.
+    ///
+    /// The fields Ring sends for most devices are picked out where they're present, and the
+    /// entire device is kept in `raw`.
+    Other {
+        /// The kind of device, as named by Ring (i.e. the `kind` field of the device).
+        kind: String,
+
         /// The ID of the device.
-        id: usize,
+        id: Option<u64>,
 
         /// The ID of the location the device is in.
-        location_id: String,
+        location_id: Option<String>,
 
         /// The description of the device.
-        description: String,
+        description: Option<String>,
 
-        #[serde(flatten)]
-        #[allow(missing_docs)]
-        extra: HashMap<String, Value>,
+        /// The device, as sent by Ring.
+        raw: Value,
     },
+}
 
-    /// A device which is yet to be mapped by the crate.
+/// The fields Ring sends for every mapped device, whichever kind of device it is.
+#[derive(Debug, Deserialize)]
+pub struct DeviceFields {
+    /// The ID of the device.
+    pub id: u64,
+
+    /// The ID of the location the device is in.
+    ///
+    /// See [`crate::location::Location`]
+    pub location_id: String,
+
+    /// The description of the device.
+    pub description: String,
+
+    /// The remaining battery, as a percentage, if the device is battery powered.
+    #[serde(default, deserialize_with = "battery_life")]
+    pub battery_life: Option<u8>,
+
+    /// The version of the firmware installed on the device (or `Up to Date`).
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+
+    /// The alerts reported for the device (such as whether it is connected).
+    #[serde(default, deserialize_with = "nullable")]
+    pub alerts: DeviceAlerts,
+
+    /// The settings of the device.
+    #[serde(default, deserialize_with = "nullable")]
+    pub settings: HashMap<String, Value>,
+
+    /// The features the device supports.
+    #[serde(default, deserialize_with = "nullable")]
+    pub features: HashMap<String, Value>,
+
+    #[serde(flatten)]
+    #[allow(missing_docs)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl DeviceData {
+    /// The variant for a kind of device (as named by Ring), or `None` if the kind is yet to be
+    /// mapped.
+    fn variant_for(kind: &str) -> Option<fn(DeviceFields) -> Self> {
+        let variant: fn(DeviceFields) -> Self = match kind {
+            "cocoa_camera" => |fields| Self::CocoaCamera { fields },
+            "doorbell_graham_cracker" => |fields| Self::DoorbellGrahamCracker { fields },
+            "doorbot"
+            | "doorbell"
+            | "doorbell_v3"
+            | "doorbell_v4"
+            | "doorbell_v5"
+            | "doorbell_scallop"
+            | "doorbell_scallop_lite"
+            | "doorbell_oyster"
+            | "doorbell_portal"
+            | "cocoa_doorbell"
+            | "cocoa_doorbell_v2"
+            | "lpd_v1"
+            | "lpd_v2"
+            | "lpd_v4"
+            | "jbox_v1" => |fields| Self::Doorbell { fields },
+            "stickup_cam"
+            | "stickup_cam_v3"
+            | "stickup_cam_v4"
+            | "stickup_cam_elite"
+            | "stickup_cam_lunar"
+            | "stickup_cam_longfin"
+            | "stickup_cam_mini" => |fields| Self::StickUpCam { fields },
+            "hp_cam_v1" | "floodlight_v1" | "floodlight_v2" | "floodlight_pro"
+            | "cocoa_floodlight" => |fields| Self::FloodlightCam { fields },
+            "hp_cam_v2" | "spotlightw_v2" | "cocoa_spotlight" => {
+                |fields| Self::SpotlightCam { fields }
+            }
+            "chime" | "chime_v2" => |fields| Self::Chime { fields },
+            "chime_pro" | "chime_pro_v2" => |fields| Self::ChimePro { fields },
+            "intercom_handset_audio" | "intercom_handset_video" => {
+                |fields| Self::Intercom { fields }
+            }
+            "base_station_v1" => |fields| Self::BaseStationV1 { fields },
+            "beams_bridge_v1" => |fields| Self::BeamsBridge { fields },
+            "beams_ct200_transformer"
+            | "beams_pathlight"
+            | "beams_spotlight"
+            | "beams_floodlight"
+            | "beams_motion_sensor" => |fields| Self::Beam { fields },
+            _ => return None,
+        };
+
+        Some(variant)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceData {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+
+        let kind = raw["kind"]
+            .as_str()
+            .ok_or_else(|| D::Error::missing_field("kind"))?;
+
+        if let Some(variant) = Self::variant_for(kind) {
+            // Mapped kinds are decoded in full, so a device which doesn't match its variant is
+            // reported rather than hidden away as an unmapped device.
+            let mut fields = DeviceFields::deserialize(&raw).map_err(D::Error::custom)?;
+
+            // The kind is already known from the variant.
+            fields.extra.remove("kind");
+
+            return Ok(variant(fields));
+        }
+
+        Ok(Self::Other {
+            kind: kind.to_string(),
+            id: raw["id"].as_u64(),
+            location_id: raw["location_id"].as_str().map(ToString::to_string),
+            description: raw["description"].as_str().map(ToString::to_string),
+            raw,
+        })
+    }
+}
+
+/// The alerts Ring reports for a device.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeviceAlerts {
+    /// Whether the device is connected to Ring.
+    pub connection: Option<ConnectionStatus>,
+
+    #[serde(flatten)]
+    #[allow(missing_docs)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Whether a device is connected to Ring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ConnectionStatus {
+    /// The device is connected.
+    Online,
+
+    /// The device has lost its connection.
+    Offline,
+
+    /// A status which is yet to be mapped by the crate.
     #[serde(other)]
+    Unknown,
+}
+
+/// Decode the battery life of a device, which Ring sends as either a number or a string (and
+/// which is missing, or `null`, for devices which aren't battery powered).
+///
+/// A battery life which isn't a percentage is ignored, rather than failing the entire device.
+fn battery_life<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+
+    let percentage = value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|value| value.parse().ok()));
+
+    Ok(percentage.and_then(|percentage| u8::try_from(percentage).ok()))
+}
+
+/// Decode a field which Ring sometimes sends as `null`, in place of its default.
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// The list a device was returned in by Ring.
+///
+/// Ring groups devices by what they are, and by whether they're owned by the account or have
+/// been shared with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeviceCategory {
+    /// Doorbells owned by the account.
+    Doorbots,
+
+    /// Doorbells which have been shared with the account (i.e. the account is a shared user).
+    AuthorizedDoorbots,
+
+    /// Chimes (such as a Chime Pro).
+    Chimes,
+
+    /// Cameras (such as a Stick Up Cam, or a Floodlight Cam).
+    StickupCams,
+
+    /// Alarm base stations.
+    BaseStations,
+
+    /// Smart Lighting devices.
+    Beams,
+
+    /// Smart Lighting bridges.
+    BeamsBridges,
+
+    /// Any other devices (such as an Intercom).
     Other,
 }
 
 /// A Device which is enabled in a Ring account.
 pub struct Device {
+    pub(crate) session: Client,
+
     #[allow(missing_docs)]
     pub data: DeviceData,
+
+    kind: String,
+    category: DeviceCategory,
 }
 
 impl Debug for Device {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Device").field("data", &self.data).finish()
+        f.debug_struct("Device")
+            .field("kind", &self.kind)
+            .field("category", &self.category)
+            .field("data", &self.data)
+            .finish_non_exhaustive()
     }
 }
 
 impl Device {
-    pub(crate) const fn new(data: DeviceData) -> Self {
-        Self { data }
+    pub(crate) fn new(session: Client, device: ListedDevice) -> Self {
+        Self {
+            session,
+            data: device.data,
+            kind: device.kind,
+            category: device.category,
+        }
+    }
+
+    /// The ID of the device.
+    ///
+    /// This is only `None` for a device which is yet to be mapped by the crate, and which Ring
+    /// didn't send an ID for.
+    #[must_use]
+    pub const fn id(&self) -> Option<u64> {
+        self.data.id()
+    }
+
+    /// The ID of the location the device is in (see [`crate::location::Location::id`]).
+    #[must_use]
+    pub fn location_id(&self) -> Option<&str> {
+        self.data.location_id()
+    }
+
+    /// The description of the device (i.e. the name given to it in the Ring app).
+    #[must_use]
+    pub fn description(&self) -> Option<&str> {
+        match &self.data {
+            DeviceData::Other { description, .. } => description.as_deref(),
+            data => data.fields().map(|fields| fields.description.as_str()),
+        }
+    }
+
+    /// The kind of device, as named by Ring (i.e. `doorbell_v4`).
+    ///
+    /// This is more specific than the variant of [`DeviceData`], which can cover a range of
+    /// models.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The remaining battery, as a percentage, or `None` if the device isn't battery powered.
+    #[must_use]
+    pub fn battery_level(&self) -> Option<u8> {
+        match &self.data {
+            DeviceData::Other { raw, .. } => battery_life(&raw["battery_life"]).ok().flatten(),
+            data => data.fields().and_then(|fields| fields.battery_life),
+        }
+    }
+
+    /// The version of the firmware installed on the device (or `Up to Date`).
+    #[must_use]
+    pub fn firmware_version(&self) -> Option<&str> {
+        match &self.data {
+            DeviceData::Other { raw, .. } => raw["firmware_version"].as_str(),
+            data => data
+                .fields()
+                .and_then(|fields| fields.firmware_version.as_deref()),
+        }
+    }
+
+    /// Whether the device is connected to Ring, or `None` if Ring didn't say.
+    #[must_use]
+    pub fn is_online(&self) -> Option<bool> {
+        let connection = match &self.data {
+            DeviceData::Other { raw, .. } => {
+                ConnectionStatus::deserialize(&raw["alerts"]["connection"]).ok()
+            }
+            data => data.fields().and_then(|fields| fields.alerts.connection),
+        };
+
+        match connection? {
+            ConnectionStatus::Online => Some(true),
+            ConnectionStatus::Offline => Some(false),
+            ConnectionStatus::Unknown => None,
+        }
+    }
+
+    /// The list the device was returned in by Ring, which says (for example) whether a
+    /// doorbell is owned by the account, or shared with it.
+    #[must_use]
+    pub const fn category(&self) -> DeviceCategory {
+        self.category
     }
 }
 
-#[derive(Deserialize)]
+impl DeviceData {
+    /// The ID of the device, if it has one.
+    pub(crate) const fn id(&self) -> Option<u64> {
+        match self {
+            Self::Other { id, .. } => *id,
+            data => match data.fields() {
+                Some(fields) => Some(fields.id),
+                None => None,
+            },
+        }
+    }
+
+    /// The ID of the location the device is in, if it is in one.
+    pub(crate) fn location_id(&self) -> Option<&str> {
+        match self {
+            Self::Other { location_id, .. } => location_id.as_deref(),
+            data => data.fields().map(|fields| fields.location_id.as_str()),
+        }
+    }
+
+    /// A feature of the device (i.e. `show_recordings`).
+    pub(crate) fn feature(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Other { raw, .. } => raw["features"].get(name),
+            data => data.fields()?.features.get(name),
+        }
+    }
+
+    /// A setting of the device (i.e. `floodlight_settings`).
+    pub(crate) fn setting(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Other { raw, .. } => raw["settings"].get(name),
+            data => data.fields()?.settings.get(name),
+        }
+    }
+
+    /// Any other field Ring sent for the device (i.e. `led_status`).
+    pub(crate) fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Other { raw, .. } => raw.get(name),
+            data => data.fields()?.extra.get(name),
+        }
+    }
+
+    /// The fields shared by every mapped device, or `None` if the device is yet to be mapped.
+    #[must_use]
+    pub const fn fields(&self) -> Option<&DeviceFields> {
+        match self {
+            Self::CocoaCamera { fields }
+            | Self::DoorbellGrahamCracker { fields }
+            | Self::Doorbell { fields }
+            | Self::StickUpCam { fields }
+            | Self::FloodlightCam { fields }
+            | Self::SpotlightCam { fields }
+            | Self::Chime { fields }
+            | Self::ChimePro { fields }
+            | Self::Intercom { fields }
+            | Self::BaseStationV1 { fields }
+            | Self::BeamsBridge { fields }
+            | Self::Beam { fields } => Some(fields),
+            Self::Other { .. } => None,
+        }
+    }
+}
+
+/// A device as listed by Ring, before it is attached to a [`Client`].
+pub(crate) struct ListedDevice {
+    pub(crate) data: DeviceData,
+    kind: String,
+    category: DeviceCategory,
+}
+
+impl ListedDevice {
+    fn decode(category: DeviceCategory, raw: &Value) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            data: Deserialize::deserialize(raw)?,
+            kind: raw["kind"].as_str().unwrap_or_default().to_string(),
+            category,
+        })
+    }
+}
+
+/// The devices in the account, grouped into lists by Ring.
+///
+/// Ring leaves out lists which the account has no devices in, so every list is optional.
+#[derive(Default, Deserialize)]
+#[serde(default)]
 struct Response {
-    doorbots: Vec<DeviceData>,
-    authorized_doorbots: Vec<DeviceData>,
-    chimes: Vec<DeviceData>,
-    stickup_cams: Vec<DeviceData>,
-    base_stations: Vec<DeviceData>,
-    beams: Vec<DeviceData>,
-    beams_bridges: Vec<DeviceData>,
-    other: Vec<DeviceData>,
+    doorbots: Vec<Value>,
+    authorized_doorbots: Vec<Value>,
+    chimes: Vec<Value>,
+    stickup_cams: Vec<Value>,
+    base_stations: Vec<Value>,
+    beams: Vec<Value>,
+    beams_bridges: Vec<Value>,
+    other: Vec<Value>,
 }
 
 impl RingApi {
-    pub async fn get_device_data(&self, tokens: &Tokens) -> Result<Vec<DeviceData>, ApiError> {
+    /// Get every device in the account, decoding each device separately so that one device
+    /// which can't be decoded doesn't prevent the others from being returned.
+    pub async fn get_device_data(&self) -> Result<Listing<ListedDevice>, ApiError> {
         let response = self
-            .client
-            .get(helper::url::get_base_url(&Url::Devices))
-            .header("User-Agent", self.operating_system.get_user_agent())
-            .bearer_auth(&tokens.access_token)
-            .send()
-            .await?
-            .json::<Response>()
+            .request::<Response>(|client| client.get(self.endpoints.get_base_url(&Url::Devices)))
             .await?;
 
-        Ok(Vec::new()
-            .into_iter()
-            .chain(response.doorbots)
-            .chain(response.authorized_doorbots)
-            .chain(response.chimes)
-            .chain(response.stickup_cams)
-            .chain(response.base_stations)
-            .chain(response.beams)
-            .chain(response.beams_bridges)
-            .chain(response.other)
-            .collect())
+        let mut listing = Listing::default();
+
+        for (category, devices) in [
+            (DeviceCategory::Doorbots, response.doorbots),
+            (
+                DeviceCategory::AuthorizedDoorbots,
+                response.authorized_doorbots,
+            ),
+            (DeviceCategory::Chimes, response.chimes),
+            (DeviceCategory::StickupCams, response.stickup_cams),
+            (DeviceCategory::BaseStations, response.base_stations),
+            (DeviceCategory::Beams, response.beams),
+            (DeviceCategory::BeamsBridges, response.beams_bridges),
+            (DeviceCategory::Other, response.other),
+        ] {
+            listing.append(Listing::decode_with(devices, |raw| {
+                ListedDevice::decode(category, raw)
+            }));
+        }
+
+        Ok(listing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_keeping_unmapped_devices() {
+        let raw = json!({
+            "kind": "mystery_device",
+            "id": 12345,
+            "location_id": "mock-location-id",
+            "description": "Front Gate",
+            "firmware_version": "1.0.0"
+        });
+
+        let DeviceData::Other {
+            kind,
+            id,
+            location_id,
+            description,
+            raw: kept,
+        } = serde_json::from_value::<DeviceData>(raw.clone())
+            .expect("Unmapped devices should be decoded")
+        else {
+            panic!("Expected an unmapped device");
+        };
+
+        assert_eq!(kind, "mystery_device");
+        assert_eq!(id, Some(12345));
+        assert_eq!(location_id.as_deref(), Some("mock-location-id"));
+        assert_eq!(description.as_deref(), Some("Front Gate"));
+        assert_eq!(kept, raw);
+    }
+
+    #[test]
+    fn test_decoding_shared_fields() {
+        let device = serde_json::from_value::<DeviceData>(json!({
+            "kind": "stickup_cam_v4",
+            "id": 1,
+            "location_id": "mock-location-id",
+            "description": "Garden",
+            "battery_life": "87",
+            "firmware_version": "Up to Date",
+            "alerts": { "connection": "online" },
+            "settings": null,
+            "features": { "motions_enabled": true },
+            "owned": true
+        }))
+        .expect("The device should be decoded");
+
+        let DeviceData::StickUpCam { fields } = device else {
+            panic!("Expected a Stick Up Cam");
+        };
+
+        assert_eq!(fields.battery_life, Some(87));
+        assert_eq!(fields.firmware_version.as_deref(), Some("Up to Date"));
+        assert_eq!(fields.alerts.connection, Some(ConnectionStatus::Online));
+        assert!(fields.settings.is_empty());
+        assert_eq!(fields.features["motions_enabled"], true);
+        assert_eq!(fields.extra["owned"], true);
+        assert!(!fields.extra.contains_key("kind"));
+    }
+
+    #[test]
+    fn test_decoding_mapped_devices_in_full() {
+        let decode = |kind: &str| {
+            serde_json::from_value::<DeviceData>(json!({
+                "kind": kind,
+                "id": 1,
+                "location_id": "mock-location-id",
+                "description": "Mock Device"
+            }))
+            .expect("Mapped devices should be decoded")
+        };
+
+        assert!(matches!(
+            decode("cocoa_camera"),
+            DeviceData::CocoaCamera { .. }
+        ));
+        assert!(matches!(
+            decode("doorbell_graham_cracker"),
+            DeviceData::DoorbellGrahamCracker { .. }
+        ));
+        assert!(matches!(
+            decode("stickup_cam_mini"),
+            DeviceData::StickUpCam { .. }
+        ));
+        assert!(matches!(
+            decode("cocoa_floodlight"),
+            DeviceData::FloodlightCam { .. }
+        ));
+        assert!(matches!(
+            decode("cocoa_spotlight"),
+            DeviceData::SpotlightCam { .. }
+        ));
+        assert!(matches!(decode("chime_v2"), DeviceData::Chime { .. }));
+        assert!(matches!(
+            decode("chime_pro_v2"),
+            DeviceData::ChimePro { .. }
+        ));
+        assert!(matches!(
+            decode("intercom_handset_video"),
+            DeviceData::Intercom { .. }
+        ));
+        assert!(matches!(
+            decode("base_station_v1"),
+            DeviceData::BaseStationV1 { .. }
+        ));
+        assert!(matches!(
+            decode("beams_bridge_v1"),
+            DeviceData::BeamsBridge { .. }
+        ));
+        assert!(matches!(
+            decode("beams_motion_sensor"),
+            DeviceData::Beam { .. }
+        ));
+
+        for kind in [
+            "doorbell_v5",
+            "doorbell_scallop",
+            "doorbell_scallop_lite",
+            "doorbell_oyster",
+            "doorbell_portal",
+            "cocoa_doorbell",
+            "cocoa_doorbell_v2",
+        ] {
+            assert!(
+                matches!(decode(kind), DeviceData::Doorbell { .. }),
+                "Expected {kind} to be decoded as a doorbell"
+            );
+        }
+
+        assert!(
+            serde_json::from_value::<DeviceData>(json!({
+                "kind": "cocoa_camera",
+                "id": 1,
+                "location_id": "mock-location-id"
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_decoding_device_lists_which_are_left_out() {
+        let response = serde_json::from_value::<Response>(json!({
+            "doorbots": [{ "kind": "doorbell_v4" }],
+            "chimes": []
+        }))
+        .expect("Missing lists should be treated as empty");
+
+        assert_eq!(response.doorbots.len(), 1);
+        assert!(response.beams_bridges.is_empty());
     }
 }
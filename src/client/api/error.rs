@@ -1,3 +1,6 @@
+use crate::location::{Message, Zid};
+use reqwest::StatusCode;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors which can occur when trying to communicate with the Ring API.
@@ -23,6 +26,103 @@ pub enum ApiError {
     #[error("An error occurred while trying to refresh the authentication tokens")]
     AuthenticationRefreshFailed(crate::client::authentication::AuthenticationError),
 
+    /// Ring rejected the access token, even after the tokens were refreshed.
+    #[error("Ring rejected the access token, even after refreshing the authentication tokens")]
+    Unauthorized,
+
+    /// Ring is rate limiting requests (`429 Too Many Requests`).
+    #[error("Ring is rate limiting requests")]
+    RateLimited {
+        /// How long Ring asked to wait before trying again, if it said.
+        retry_after: Option<Duration>,
+
+        /// The raw body of the response.
+        body: String,
+    },
+
+    /// The requested resource does not exist (`404 Not Found`).
+    #[error("The requested resource could not be found")]
+    NotFound {
+        /// The raw body of the response.
+        body: String,
+    },
+
+    /// Ring failed to handle the request (`5xx`).
+    #[error("Ring failed to handle the request ({status}): {body}")]
+    ServerError {
+        /// The status of the response.
+        status: StatusCode,
+
+        /// The raw body of the response.
+        body: String,
+    },
+
+    /// Ring responded with an unsuccessful status which doesn't have a more specific error.
+    #[error("Ring responded with an unexpected status ({status}): {body}")]
+    UnexpectedStatus {
+        /// The status of the response.
+        status: StatusCode,
+
+        /// The raw body of the response.
+        body: String,
+    },
+
+    /// Ring refused to change the mode of the alarm.
+    #[error("Ring refused to change the alarm mode: {reason}")]
+    AlarmModeRefused {
+        /// Why the change was refused.
+        reason: String,
+
+        /// The sensors which are faulted, and need to be bypassed before the alarm can be armed.
+        faulted_sensors: Vec<Zid>,
+    },
+
+    /// A connection to one location was used to make a request about another.
+    #[error("The connection is to location {connection}, not location {location}")]
+    WrongLocation {
+        /// The ID of the location the request was about.
+        location: String,
+
+        /// The ID of the location the connection is to.
+        connection: String,
+    },
+
+    /// The WebSocket connection was closed before Ring responded.
+    #[error("The connection to Ring was closed before a response was received")]
+    ConnectionClosed,
+
+    /// The device does not support the requested operation (such as fetching the history of a
+    /// device which is yet to be mapped by the crate).
+    #[error("The device does not support {0}")]
+    UnsupportedDevice(&'static str),
+
+    /// Ring responded to a request sent over a WebSocket with an unexpected message.
+    #[error("Ring responded with an unexpected message: {0:?}")]
+    UnexpectedMessage(Message),
+
+    /// Ring did not respond to a request sent over a WebSocket in time.
+    #[error("Ring did not respond to the request in time")]
+    RequestTimedOut,
+
+    /// Ring did not capture a new snapshot from a camera in time.
+    #[error("Ring did not capture a new snapshot in time")]
+    SnapshotNotUpdated,
+
+    /// Writing a download (such as a recording) to its destination failed.
+    #[error("An error occurred while writing the download: {0}")]
+    WriteFailed(#[source] std::io::Error),
+
+    /// A download was resumed from a different point than where it stopped (according to the
+    /// `Content-Range` of the response), so it couldn't be continued.
+    #[error("The download stopped after {written} bytes, but was resumed from {start:?}")]
+    ResumedFromWrongPosition {
+        /// The number of bytes which had been written when the download stopped.
+        written: u64,
+
+        /// The byte the download was resumed from, if the response said.
+        start: Option<u64>,
+    },
+
     /// An attempt to write to a closed WebSocket sink was made.
     #[error("An error occurred while sending a message")]
     SinkAlreadyClosed,
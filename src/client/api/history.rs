@@ -0,0 +1,486 @@
+use crate::Client;
+use crate::client::api::error::ApiError;
+use crate::client::api::{Listing, RingApi};
+use crate::device::Device;
+use crate::helper::url::Url;
+use crate::location::Location;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+
+/// The number of items requested from Ring at a time.
+const PAGE_SIZE: u32 = 50;
+
+/// What caused an item in the history of a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum HistoryKind {
+    /// The doorbell was pressed.
+    Ding,
+
+    /// Motion was detected.
+    Motion,
+
+    /// Live view was started on demand (i.e. from the app).
+    OnDemand,
+
+    /// A kind which is yet to be mapped by the crate.
+    #[serde(other)]
+    Other,
+}
+
+impl HistoryKind {
+    /// The name Ring uses for the kind, or `None` for kinds which are yet to be mapped.
+    const fn name(self) -> Option<&'static str> {
+        match self {
+            Self::Ding => Some("ding"),
+            Self::Motion => Some("motion"),
+            Self::OnDemand => Some("on_demand"),
+            Self::Other => None,
+        }
+    }
+}
+
+/// Whether the recording of an item in the history of a device can be downloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum RecordingStatus {
+    /// The recording is ready to be downloaded.
+    Ready,
+
+    /// The recording is still being processed by Ring.
+    Processing,
+
+    /// A status which is yet to be mapped by the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An item in the history of a device (such as the doorbell being pressed).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "Item")]
+pub struct HistoryItem {
+    /// The ID of the item.
+    ///
+    /// This is also the ID of the recording of the item (the ding ID).
+    pub id: u64,
+
+    /// What caused the item.
+    pub kind: HistoryKind,
+
+    /// When the item occurred.
+    pub created_at: DateTime<Utc>,
+
+    /// Whether the item was answered (i.e. the doorbell was answered using the app).
+    pub answered: bool,
+
+    /// The status of the recording of the item, if it has one.
+    pub recording_status: Option<RecordingStatus>,
+
+    /// The ID of the device the item occurred on.
+    pub device_id: Option<u64>,
+
+    #[allow(missing_docs)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// Selects which items are returned from the history of a device or location.
+///
+/// # Example
+///
+/// ```
+/// use ring_client::history::{HistoryKind, HistoryQuery};
+///
+/// let query = HistoryQuery {
+///     limit: Some(10),
+///     kind: Some(HistoryKind::Ding),
+///     ..HistoryQuery::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    /// The most items to return. By default, the entire history is returned.
+    pub limit: Option<u32>,
+
+    /// Only return items older than the item with this ID (i.e. to resume from where a previous
+    /// query finished).
+    pub older_than: Option<u64>,
+
+    /// Only return items of this kind.
+    pub kind: Option<HistoryKind>,
+}
+
+/// The shape of a history item sent by Ring.
+#[derive(Deserialize)]
+struct Item {
+    id: u64,
+    kind: HistoryKind,
+    created_at: DateTime<Utc>,
+
+    #[serde(default)]
+    answered: bool,
+
+    recording: Option<Recording>,
+    doorbot: Option<Doorbot>,
+
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Recording {
+    status: RecordingStatus,
+}
+
+#[derive(Deserialize)]
+struct Doorbot {
+    id: u64,
+}
+
+impl From<Item> for HistoryItem {
+    fn from(item: Item) -> Self {
+        Self {
+            id: item.id,
+            kind: item.kind,
+            created_at: item.created_at,
+            answered: item.answered,
+            recording_status: item.recording.map(|recording| recording.status),
+            device_id: item.doorbot.map(|doorbot| doorbot.id),
+            extra: item.extra,
+        }
+    }
+}
+
+/// The shape of a page of location events sent by Ring.
+#[derive(Deserialize)]
+struct LocationEvents {
+    /// Decoded separately (see [`LocationEvent`]), so a single bad event doesn't lose the page.
+    events: Vec<Value>,
+
+    #[serde(default)]
+    meta: Meta,
+}
+
+#[derive(Default, Deserialize)]
+struct Meta {
+    /// Used to request the next page, or missing (or empty) once there are no more pages.
+    pagination_key: Option<String>,
+}
+
+/// The shape of an event in the history of a location sent by Ring.
+#[derive(Deserialize)]
+struct LocationEvent {
+    ding_id: u64,
+    kind: HistoryKind,
+    created_at: DateTime<Utc>,
+
+    /// How the event was handled (i.e. `accepted`, if the doorbell was answered).
+    state: Option<String>,
+
+    recording_status: Option<RecordingStatus>,
+    doorbot_id: Option<u64>,
+
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+impl From<LocationEvent> for HistoryItem {
+    fn from(event: LocationEvent) -> Self {
+        Self {
+            id: event.ding_id,
+            kind: event.kind,
+            created_at: event.created_at,
+            answered: event.state.as_deref() == Some("accepted"),
+            recording_status: event.recording_status,
+            device_id: event.doorbot_id,
+            extra: event.extra,
+        }
+    }
+}
+
+/// Where a history is read from.
+#[derive(Clone, Copy)]
+enum Source {
+    /// The history of a single device, which is paged through by the ID of the last item.
+    Device,
+
+    /// The events in a location, which are paged through using the key Ring returns with
+    /// each page.
+    Location,
+}
+
+/// Fetches the history from Ring a page at a time, as it is read.
+struct Pager {
+    session: Client,
+    url: String,
+    source: Source,
+    query: HistoryQuery,
+    buffer: VecDeque<HistoryItem>,
+    older_than: Option<u64>,
+    pagination_key: Option<String>,
+    returned: u32,
+    exhausted: bool,
+}
+
+impl Pager {
+    const fn new(session: Client, url: String, source: Source, query: HistoryQuery) -> Self {
+        Self {
+            session,
+            url,
+            source,
+            query,
+            buffer: VecDeque::new(),
+            older_than: query.older_than,
+            pagination_key: None,
+            returned: 0,
+            exhausted: false,
+        }
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<HistoryItem, ApiError>> + Send + 'static {
+        futures_util::stream::unfold(self, |mut pager| async move {
+            pager.next().await.map(|item| (item, pager))
+        })
+    }
+
+    async fn next(&mut self) -> Option<Result<HistoryItem, ApiError>> {
+        loop {
+            if self.query.limit.is_some_and(|limit| self.returned >= limit) {
+                return None;
+            }
+
+            if let Some(item) = self.buffer.pop_front() {
+                self.returned += 1;
+                return Some(Ok(item));
+            }
+
+            if self.exhausted {
+                return None;
+            }
+
+            if let Err(error) = self.fetch().await {
+                self.exhausted = true;
+                return Some(Err(error));
+            }
+        }
+    }
+
+    /// Fetch the next page of history, keeping the items which match the query.
+    async fn fetch(&mut self) -> Result<(), ApiError> {
+        let api = &self.session.inner.api;
+
+        let page = match self.source {
+            Source::Device => {
+                let page = api
+                    .get_history(&self.url, PAGE_SIZE, self.older_than)
+                    .await?;
+
+                // Page on from the last item, even if it couldn't be decoded. Ring signals the
+                // end of the history with an empty page.
+                let last_id = page.iter().rev().find_map(|item| item["id"].as_u64());
+
+                self.exhausted = last_id.is_none();
+                self.older_than = last_id.or(self.older_than);
+
+                Listing::<HistoryItem>::decode(page).items
+            }
+            Source::Location => {
+                let page = api
+                    .get_location_events(
+                        &self.url,
+                        PAGE_SIZE,
+                        &self.query,
+                        self.pagination_key.as_deref(),
+                    )
+                    .await?;
+
+                self.pagination_key = page.meta.pagination_key.filter(|key| !key.is_empty());
+                self.exhausted = page.events.is_empty() || self.pagination_key.is_none();
+
+                Listing::<LocationEvent>::decode(page.events)
+                    .map(HistoryItem::from)
+                    .items
+            }
+        };
+
+        let kind = self.query.kind;
+
+        self.buffer.extend(
+            page.into_iter()
+                .filter(|item| kind.is_none_or(|kind| item.kind == kind)),
+        );
+
+        Ok(())
+    }
+}
+
+impl Device {
+    /// Read the history of the device (such as dings and motion), newest first.
+    ///
+    /// The history is fetched from Ring a page at a time while the stream is read, so it can
+    /// be used to backfill the entire history of a device. Items which could not be decoded
+    /// are skipped (and logged).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::history::HistoryQuery;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let devices = client.get_devices()
+    ///      .await
+    ///      .expect("Getting devices should not fail");
+    ///
+    /// let device = devices
+    ///      .first()
+    ///      .expect("There should be at least one device");
+    ///
+    /// let history = device.history(HistoryQuery::default())
+    ///      .expect("The device should have a history");
+    ///
+    /// let mut history = std::pin::pin!(history);
+    ///
+    /// while let Some(item) = history.next().await {
+    ///     println!("{:#?}", item.expect("Reading the history should not fail"));
+    /// }
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::UnsupportedDevice`] if the device doesn't have a camera (see
+    /// [`Device::as_camera`]), as only cameras and doorbells have a history.
+    ///
+    /// The stream returns an error (and then ends) if a page of the history could not be
+    /// fetched.
+    pub fn history(
+        &self,
+        query: HistoryQuery,
+    ) -> Result<impl Stream<Item = Result<HistoryItem, ApiError>> + Send + 'static, ApiError> {
+        let device_id = self
+            .id()
+            .filter(|_| self.as_camera().is_some())
+            .ok_or(ApiError::UnsupportedDevice("history"))?;
+
+        let url = self
+            .session
+            .inner
+            .api
+            .endpoints
+            .get_base_url(&Url::DeviceHistory { device_id });
+
+        Ok(Pager::new(self.session.clone(), url, Source::Device, query).into_stream())
+    }
+}
+
+impl Location {
+    /// Read the history of every device in the location (such as dings and motion), newest
+    /// first.
+    ///
+    /// See [`Device::history`].
+    ///
+    /// # Errors
+    ///
+    /// The stream returns an error (and then ends) if a page of the history could not be
+    /// fetched.
+    pub fn history(
+        &self,
+        query: HistoryQuery,
+    ) -> impl Stream<Item = Result<HistoryItem, ApiError>> + Send + 'static {
+        let url = self
+            .session
+            .inner
+            .api
+            .endpoints
+            .get_base_url(&Url::LocationEvents {
+                location_id: self.id(),
+            });
+
+        Pager::new(self.session.clone(), url, Source::Location, query).into_stream()
+    }
+}
+
+impl RingApi {
+    pub(crate) async fn get_history(
+        &self,
+        url: &str,
+        limit: u32,
+        older_than: Option<u64>,
+    ) -> Result<Vec<Value>, ApiError> {
+        self.request(|client| {
+            let request = client.get(url).query(&[("limit", limit)]);
+
+            match older_than {
+                Some(older_than) => request.query(&[("older_than", older_than)]),
+                None => request,
+            }
+        })
+        .await
+    }
+
+    async fn get_location_events(
+        &self,
+        url: &str,
+        limit: u32,
+        query: &HistoryQuery,
+        pagination_key: Option<&str>,
+    ) -> Result<LocationEvents, ApiError> {
+        self.request(|client| {
+            let mut request = client.get(url).query(&[("limit", limit)]);
+
+            if let Some(kind) = query.kind.and_then(HistoryKind::name) {
+                request = request.query(&[("kind", kind)]);
+            }
+
+            // The item to start from only applies to the first page, after which Ring's
+            // pagination key is followed.
+            match (pagination_key, query.older_than) {
+                (Some(key), _) => request.query(&[("pagination_key", key)]),
+                (None, Some(older_than)) => request.query(&[("older_than_id", older_than)]),
+                (None, None) => request,
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decoding_history_item() {
+        let item = serde_json::from_value::<HistoryItem>(json!({
+            "id": 7_000_000_000_000_000_001_u64,
+            "kind": "on_demand",
+            "created_at": "2024-01-01T00:00:00.000Z",
+            "answered": false,
+            "favorite": false,
+            "recording": { "status": "ready" },
+            "doorbot": { "id": 12345, "description": "Front Door" }
+        }))
+        .expect("History item should be decoded");
+
+        assert_eq!(item.id, 7_000_000_000_000_000_001);
+        assert_eq!(item.kind, HistoryKind::OnDemand);
+        assert_eq!(item.recording_status, Some(RecordingStatus::Ready));
+        assert_eq!(item.device_id, Some(12345));
+        assert_eq!(item.extra["favorite"], false);
+    }
+}
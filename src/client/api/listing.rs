@@ -0,0 +1,107 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+/// A list of items (such as devices) returned by Ring, where each item is decoded on its own.
+///
+/// Ring occasionally returns items which the crate is unable to decode (for example, a device
+/// which is missing a field). Rather than failing the entire list, those items are collected
+/// in [`Listing::errors`], alongside the items which were decoded successfully.
+#[derive(Debug)]
+pub struct Listing<T> {
+    /// The items which were decoded successfully.
+    pub items: Vec<T>,
+
+    /// The items which could not be decoded.
+    pub errors: Vec<ItemError>,
+}
+
+impl<T> Default for Listing<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// An item in a list returned by Ring which could not be decoded.
+#[derive(Error, Debug)]
+#[error("An item returned by Ring could not be decoded: {error}")]
+pub struct ItemError {
+    /// Why the item could not be decoded.
+    #[source]
+    pub error: serde_json::Error,
+
+    /// The raw JSON of the item, as returned by Ring.
+    pub raw: Value,
+}
+
+impl<T: DeserializeOwned> Listing<T> {
+    /// Decode each item in a list separately.
+    pub(crate) fn decode(values: impl IntoIterator<Item = Value>) -> Self {
+        Self::decode_with(values, |raw| T::deserialize(raw))
+    }
+}
+
+impl<T> Listing<T> {
+    /// Decode each item in a list separately, using the given function.
+    pub(crate) fn decode_with(
+        values: impl IntoIterator<Item = Value>,
+        mut decode: impl FnMut(&Value) -> Result<T, serde_json::Error>,
+    ) -> Self {
+        let mut listing = Self::default();
+
+        for raw in values {
+            match decode(&raw) {
+                Ok(item) => listing.items.push(item),
+                Err(error) => {
+                    log::warn!("Skipping an item which could not be decoded ({error}): {raw}");
+
+                    listing.errors.push(ItemError { error, raw });
+                }
+            }
+        }
+
+        listing
+    }
+
+    /// Add the items (and errors) from another list to the end of this one.
+    pub(crate) fn append(&mut self, mut other: Self) {
+        self.items.append(&mut other.items);
+        self.errors.append(&mut other.errors);
+    }
+
+    /// Convert each of the decoded items, keeping the errors.
+    pub(crate) fn map<U>(self, f: impl FnMut(T) -> U) -> Listing<U> {
+        Listing {
+            items: self.items.into_iter().map(f).collect(),
+            errors: self.errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, PartialEq, Eq)]
+    struct Item {
+        id: u32,
+    }
+
+    #[test]
+    fn test_decoding_items_separately() {
+        let listing = Listing::<Item>::decode([
+            json!({ "id": 1 }),
+            json!({ "id": "two" }),
+            json!({ "id": 3 }),
+        ]);
+
+        assert_eq!(listing.items, [Item { id: 1 }, Item { id: 3 }]);
+        assert_eq!(listing.errors.len(), 1);
+        assert_eq!(listing.errors[0].raw, json!({ "id": "two" }));
+    }
+}
@@ -0,0 +1,630 @@
+use crate::client::api::Listing;
+use crate::client::api::error::ApiError;
+use crate::location::{Connection, Event, Filter, Listener, Location, Message};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The ID Ring uses to identify a device connected to an alarm base station.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Zid(String);
+
+impl Zid {
+    /// Create a new ID.
+    #[must_use]
+    pub fn new(zid: impl Into<String>) -> Self {
+        Self(zid.into())
+    }
+
+    /// The ID as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Zid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The mode of a Ring Alarm security panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlarmMode {
+    /// The alarm is disarmed.
+    #[serde(rename = "none")]
+    Disarmed,
+
+    /// The alarm is armed, but only some sensors (such as doors and windows) are monitored.
+    #[serde(rename = "some")]
+    Home,
+
+    /// The alarm is armed, and every sensor is monitored.
+    #[serde(rename = "all")]
+    Away,
+}
+
+/// Whether the casing of an alarm device has been opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum TamperStatus {
+    Ok,
+    Tamper,
+
+    /// A status which is yet to be mapped by the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Whether an alarm device can communicate with the base station.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub enum CommStatus {
+    Ok,
+    Error,
+    WaitForJoin,
+    WaitForRemove,
+
+    /// A status which is yet to be mapped by the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// General information about an alarm device (the `general.v2` document).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneralInfo {
+    /// The ID of the device.
+    pub zid: Zid,
+
+    /// The name of the device.
+    #[serde(default)]
+    pub name: String,
+
+    /// The type of the device (i.e. `sensor.contact`).
+    pub device_type: String,
+
+    /// The remaining battery, as a percentage.
+    pub battery_level: Option<u8>,
+
+    /// Whether the casing of the device has been opened.
+    pub tamper_status: Option<TamperStatus>,
+
+    /// Whether the device can communicate with the base station.
+    pub comm_status: Option<CommStatus>,
+
+    #[serde(flatten)]
+    #[allow(missing_docs)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The state of an alarm device (the `device.v1` document).
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeviceInfo {
+    /// Whether the sensor is faulted (i.e. a door is open, or motion is detected).
+    pub faulted: Option<bool>,
+
+    #[serde(flatten)]
+    #[allow(missing_docs)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The information Ring holds about an alarm device.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "Document")]
+pub struct AlarmDeviceData {
+    /// General information about the device.
+    pub general: GeneralInfo,
+
+    /// The state of the device.
+    pub device: DeviceInfo,
+}
+
+/// A device connected to a Ring Alarm base station.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "AlarmDeviceData")]
+#[allow(missing_docs)]
+#[non_exhaustive]
+pub enum AlarmDevice {
+    ContactSensor(AlarmDeviceData),
+    MotionSensor(AlarmDeviceData),
+    Keypad(AlarmDeviceData),
+    RangeExtender(AlarmDeviceData),
+    SmokeCoListener(AlarmDeviceData),
+    FloodFreezeSensor(AlarmDeviceData),
+    SecurityPanel(AlarmDeviceData),
+
+    /// A device which is yet to be mapped by the crate (such as the base station itself).
+    Other(AlarmDeviceData),
+}
+
+impl AlarmDevice {
+    /// The information Ring holds about the device.
+    #[must_use]
+    pub const fn data(&self) -> &AlarmDeviceData {
+        match self {
+            Self::ContactSensor(data)
+            | Self::MotionSensor(data)
+            | Self::Keypad(data)
+            | Self::RangeExtender(data)
+            | Self::SmokeCoListener(data)
+            | Self::FloodFreezeSensor(data)
+            | Self::SecurityPanel(data)
+            | Self::Other(data) => data,
+        }
+    }
+
+    /// The ID of the device.
+    #[must_use]
+    pub const fn zid(&self) -> &Zid {
+        &self.data().general.zid
+    }
+
+    /// The name of the device.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.data().general.name
+    }
+
+    /// Whether a `DataUpdate` message confirms the security panel has changed to the given mode.
+    fn is_mode_update(message: &Value, panel: &Zid, mode: AlarmMode) -> bool {
+        if message["datatype"] != "DeviceInfoDocType" {
+            return false;
+        }
+
+        message["body"].as_array().is_some_and(|documents| {
+            documents.iter().any(|document| {
+                document["general"]["v2"]["zid"] == panel.as_str()
+                    && AlarmMode::deserialize(&document["device"]["v1"]["mode"]).ok() == Some(mode)
+            })
+        })
+    }
+
+    /// Decode each of the devices in the body of a `DeviceInfoDocGetList` message separately.
+    fn from_list(message: &Value) -> Result<Listing<Self>, ApiError> {
+        Ok(Listing::decode(Vec::<Value>::deserialize(
+            &message["body"],
+        )?))
+    }
+}
+
+impl From<AlarmDeviceData> for AlarmDevice {
+    fn from(data: AlarmDeviceData) -> Self {
+        match data.general.device_type.as_str() {
+            "sensor.contact" => Self::ContactSensor(data),
+            "sensor.motion" => Self::MotionSensor(data),
+            "security-keypad" => Self::Keypad(data),
+            "range-extender.zwave" => Self::RangeExtender(data),
+            "listener.smoke-co" => Self::SmokeCoListener(data),
+            "sensor.flood-freeze" => Self::FloodFreezeSensor(data),
+            "security-panel" => Self::SecurityPanel(data),
+            _ => Self::Other(data),
+        }
+    }
+}
+
+/// The shape of a device document sent by Ring.
+#[derive(Deserialize)]
+struct Document {
+    general: General,
+
+    #[serde(default)]
+    device: Device,
+}
+
+#[derive(Deserialize)]
+struct General {
+    v2: GeneralInfo,
+}
+
+#[derive(Default, Deserialize)]
+struct Device {
+    #[serde(default)]
+    v1: DeviceInfo,
+}
+
+impl From<Document> for AlarmDeviceData {
+    fn from(document: Document) -> Self {
+        Self {
+            general: document.general.v2,
+            device: document.device.v1,
+        }
+    }
+}
+
+impl Location {
+    /// Get every device connected to the alarm base station(s) in the location.
+    ///
+    /// The device list is requested over the given connection using [`Connection::request`],
+    /// so any other events received in the meantime are still delivered through it.
+    ///
+    /// Devices which could not be decoded are skipped (and logged). Use
+    /// [`Location::get_alarm_device_listing`] to find out which devices were skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::WrongLocation`] if the connection is to another location.
+    ///
+    /// Returns an error if the request could not be sent, if the connection is closed before
+    /// Ring responds, or if Ring does not respond in time.
+    pub async fn get_alarm_devices(
+        &self,
+        connection: &Connection,
+    ) -> Result<Vec<AlarmDevice>, ApiError> {
+        Ok(self.get_alarm_device_listing(connection).await?.items)
+    }
+
+    /// Get every device connected to the alarm base station(s) in the location, along with any
+    /// devices which could not be decoded.
+    ///
+    /// Each device is decoded separately, so a single device which can't be decoded doesn't
+    /// prevent the others from being returned. The raw JSON of each device which could not be
+    /// decoded is kept in its [`crate::ItemError`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Location::get_alarm_devices`].
+    pub async fn get_alarm_device_listing(
+        &self,
+        connection: &Connection,
+    ) -> Result<Listing<AlarmDevice>, ApiError> {
+        self.check_connection(connection)?;
+
+        let mut listing = Listing::default();
+
+        for (_, devices) in Self::get_alarm_devices_by_asset(connection).await? {
+            listing.append(devices);
+        }
+
+        Ok(listing)
+    }
+
+    /// Change the mode of the alarm's security panel (i.e. to arm or disarm it), and wait for
+    /// Ring to confirm the change.
+    ///
+    /// Ring will refuse to arm the alarm while any sensors are faulted (such as a door being
+    /// open), unless they are bypassed. Bypassed sensors are ignored until the alarm is next
+    /// disarmed.
+    ///
+    /// Any other events received while waiting for the confirmation are still delivered
+    /// through the connection.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::location::AlarmMode;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let locations = client.get_locations()
+    ///      .await
+    ///      .expect("Getting locations should not fail");
+    ///
+    /// let location = locations
+    ///      .first()
+    ///      .expect("There should be at least one location");
+    ///
+    /// let mut listener = location.get_listener()
+    ///      .await
+    ///      .expect("Creating a listener should not fail");
+    ///
+    /// listener.set_alarm_mode(AlarmMode::Away, &[])
+    ///      .await
+    ///      .expect("Arming the alarm should not fail");
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::AlarmModeRefused`] if the location has no security panel, if
+    /// sensors are faulted and not bypassed, or if Ring refuses the change for another reason.
+    ///
+    /// Returns [`ApiError::WrongLocation`] if the connection is to another location.
+    ///
+    /// Returns an error if the command could not be sent, if the connection is closed before
+    /// Ring responds (or confirms the change), or if Ring does not acknowledge (and confirm)
+    /// the change in time.
+    pub async fn set_alarm_mode(
+        &self,
+        connection: &Connection,
+        mode: AlarmMode,
+        bypass_sensors: &[Zid],
+    ) -> Result<(), ApiError> {
+        self.check_connection(connection)?;
+
+        let devices = Self::get_alarm_devices_by_asset(connection).await?;
+
+        let Some((asset, panel)) = devices.iter().find_map(|(asset, devices)| {
+            devices
+                .items
+                .iter()
+                .find(|device| matches!(device, AlarmDevice::SecurityPanel(_)))
+                .map(|panel| (asset, panel.zid()))
+        }) else {
+            return Err(ApiError::AlarmModeRefused {
+                reason: "The location does not have a security panel".to_string(),
+                faulted_sensors: Vec::new(),
+            });
+        };
+
+        // Subscribe before sending the command, so the confirmation can't be missed. The
+        // subscription ends if the WebSocket closes, as the confirmation would be lost with it.
+        let mut updates = connection
+            .subscribe_until_closed(Filter::device(panel.clone()).datatype("DeviceInfoDocType"));
+
+        let response = connection
+            .request(Event::new(Message::DeviceInfoSet(json!({
+                "datatype": "DeviceInfoSetType",
+                "dst": asset,
+                "body": [{
+                    "zid": panel,
+                    "command": {
+                        "v1": [{
+                            "commandType": "security-panel.switch-mode",
+                            "data": {
+                                "mode": mode,
+                                "bypass": bypass_sensors
+                            }
+                        }]
+                    }
+                }]
+            }))))
+            .await?;
+
+        let Message::DeviceInfoSet(message) = response.message else {
+            return Err(ApiError::UnexpectedMessage(response.message));
+        };
+
+        let status = message["status"].as_i64().unwrap_or_default();
+
+        if status != 0 {
+            // Ring doesn't say which sensors stopped the alarm from being armed, so report the
+            // ones which were faulted (and not bypassed) when the command was sent.
+            let faulted_sensors = devices
+                .iter()
+                .flat_map(|(_, devices)| &devices.items)
+                .filter(|device| device.data().device.faulted == Some(true))
+                .map(AlarmDevice::zid)
+                .filter(|zid| !bypass_sensors.contains(zid))
+                .cloned()
+                .collect();
+
+            return Err(ApiError::AlarmModeRefused {
+                reason: message["error"].as_str().map_or_else(
+                    || format!("Ring responded with status {status}"),
+                    ToString::to_string,
+                ),
+                faulted_sensors,
+            });
+        }
+
+        let confirmed = async {
+            while let Some(event) = updates.recv().await {
+                if let Message::DataUpdate(message) = &event.message {
+                    if AlarmDevice::is_mode_update(message, panel, mode) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            Err(ApiError::ConnectionClosed)
+        };
+
+        tokio::time::timeout(connection.request_timeout(), confirmed)
+            .await
+            .map_err(|_| ApiError::RequestTimedOut)?
+    }
+
+    /// Check the connection is to this location, rather than another.
+    fn check_connection(&self, connection: &Connection) -> Result<(), ApiError> {
+        if connection.location_id() == self.id() {
+            return Ok(());
+        }
+
+        Err(ApiError::WrongLocation {
+            location: self.id().to_string(),
+            connection: connection.location_id().to_string(),
+        })
+    }
+
+    /// Request the devices connected to every asset reachable over the connection.
+    async fn get_alarm_devices_by_asset(
+        connection: &Connection,
+    ) -> Result<Vec<(String, Listing<AlarmDevice>)>, ApiError> {
+        let assets = connection
+            .assets()
+            .iter()
+            .map(|asset| asset.uuid().to_string())
+            .collect::<Vec<_>>();
+
+        let mut devices = Vec::new();
+
+        for asset in assets {
+            let response = connection
+                .request(Event::new(Message::DeviceInfoDocGetList(json!({
+                    "dst": asset
+                }))))
+                .await?;
+
+            match response.message {
+                Message::DeviceInfoDocGetList(message) => {
+                    devices.push((asset, AlarmDevice::from_list(&message)?));
+                }
+                message => return Err(ApiError::UnexpectedMessage(message)),
+            }
+        }
+
+        Ok(devices)
+    }
+}
+
+impl Listener {
+    /// Change the mode of the alarm's security panel, using the listener's connection.
+    ///
+    /// See [`Location::set_alarm_mode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::AlarmModeRefused`] if the location has no security panel, if
+    /// sensors are faulted and not bypassed, or if Ring refuses the change for another reason.
+    ///
+    /// Returns an error if the command could not be sent, if the connection is closed before
+    /// Ring responds, or if Ring does not acknowledge the command in time.
+    pub async fn set_alarm_mode(
+        &mut self,
+        mode: AlarmMode,
+        bypass_sensors: &[Zid],
+    ) -> Result<(), ApiError> {
+        let (location, connection) = self.parts();
+
+        location
+            .set_alarm_mode(connection, mode, bypass_sensors)
+            .await
+    }
+
+    /// Get every device connected to the alarm base station(s) in the location, using the
+    /// listener's connection.
+    ///
+    /// See [`Location::get_alarm_devices`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be sent, if the connection is closed before
+    /// Ring responds, or if Ring does not respond in time.
+    pub async fn get_alarm_devices(&mut self) -> Result<Vec<AlarmDevice>, ApiError> {
+        let (location, connection) = self.parts();
+
+        location.get_alarm_devices(connection).await
+    }
+
+    /// Get every device connected to the alarm base station(s) in the location, along with any
+    /// devices which could not be decoded, using the listener's connection.
+    ///
+    /// See [`Location::get_alarm_device_listing`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be sent, if the connection is closed before
+    /// Ring responds, or if Ring does not respond in time.
+    pub async fn get_alarm_device_listing(&mut self) -> Result<Listing<AlarmDevice>, ApiError> {
+        let (location, connection) = self.parts();
+
+        location.get_alarm_device_listing(connection).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detecting_mode_update() {
+        let panel = Zid::new("mock-zid-panel");
+
+        let update = json!({
+            "datatype": "DeviceInfoDocType",
+            "body": [{
+                "general": { "v2": { "zid": "mock-zid-panel" } },
+                "device": { "v1": { "mode": "all" } }
+            }]
+        });
+
+        assert!(AlarmDevice::is_mode_update(
+            &update,
+            &panel,
+            AlarmMode::Away
+        ));
+        assert!(!AlarmDevice::is_mode_update(
+            &update,
+            &panel,
+            AlarmMode::Home
+        ));
+        assert!(!AlarmDevice::is_mode_update(
+            &update,
+            &Zid::new("mock-zid-other"),
+            AlarmMode::Away
+        ));
+    }
+
+    #[test]
+    fn test_decoding_device_list() {
+        let listing = AlarmDevice::from_list(&json!({
+            "datatype": "DeviceInfoDocType",
+            "body": [
+                {
+                    "general": {
+                        "v2": {
+                            "zid": "mock-zid-1",
+                            "name": "Front Door",
+                            "deviceType": "sensor.contact",
+                            "batteryLevel": 100,
+                            "tamperStatus": "ok",
+                            "commStatus": "ok",
+                            "roomId": 1
+                        }
+                    },
+                    "device": {
+                        "v1": {
+                            "faulted": true
+                        }
+                    }
+                },
+                {
+                    "general": {
+                        "v2": {
+                            "zid": "mock-zid-2",
+                            "name": "Base Station",
+                            "deviceType": "hub.redsky",
+                            "commStatus": "wait-for-join"
+                        }
+                    }
+                },
+                {
+                    "general": {
+                        "v2": {
+                            "name": "Missing ID",
+                            "deviceType": "sensor.motion"
+                        }
+                    }
+                }
+            ]
+        }))
+        .expect("Device list should be valid");
+
+        let devices = &listing.items;
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(listing.errors.len(), 1);
+        assert_eq!(listing.errors[0].raw["general"]["v2"]["name"], "Missing ID");
+
+        assert!(matches!(
+            &devices[0],
+            AlarmDevice::ContactSensor(data)
+                if data.device.faulted == Some(true)
+                    && data.general.battery_level == Some(100)
+                    && data.general.tamper_status == Some(TamperStatus::Ok)
+        ));
+        assert_eq!(devices[0].zid(), &Zid::new("mock-zid-1"));
+
+        assert!(matches!(&devices[1], AlarmDevice::Other(_)));
+        assert_eq!(devices[1].name(), "Base Station");
+        assert_eq!(
+            devices[1].data().general.comm_status,
+            Some(CommStatus::WaitForJoin)
+        );
+    }
+}
@@ -0,0 +1,604 @@
+use crate::ApiError;
+use crate::location::{Event, Filter, Matcher, Message};
+use crate::ticket::Asset;
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::task::AtomicWaker;
+use futures_util::{SinkExt, Stream, StreamExt};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as SyncMutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::Utf8Bytes;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite};
+
+pub(super) type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// The most events which are kept for reading through a [`Connection`], after which the oldest
+/// are dropped.
+const EVENT_CAPACITY: usize = 1024;
+
+/// Requests which are waiting for a response from Ring, keyed by their sequence number.
+type Pending = SyncMutex<HashMap<u64, PendingRequest>>;
+
+/// A request which is waiting for a response from Ring.
+#[derive(Debug)]
+struct PendingRequest {
+    /// The name of the request, which Ring also uses for the response (i.e. its `msg` field).
+    name: String,
+    respond: oneshot::Sender<Event>,
+}
+
+/// Subscribers to the events matching a filter.
+type Subscribers = SyncMutex<Vec<Subscriber>>;
+
+#[derive(Debug)]
+struct Subscriber {
+    matcher: Matcher,
+    sender: mpsc::UnboundedSender<Event>,
+
+    /// Whether the subscription ends when the WebSocket closes, even if the connection is about
+    /// to be replaced.
+    ends_with_socket: bool,
+}
+
+/// A live connection for exchanging messages with Ring.
+///
+/// For example, to enable an Alarm system.
+///
+/// Incoming events can be read using [`Connection::next`], or by using the connection as a
+/// [`Stream`]. Events can be sent using [`Connection::send`], or from elsewhere (i.e. another
+/// task) using a [`Sender`].
+///
+/// Messages are read from Ring in the background, so that responses to requests made using
+/// [`Connection::request`] can be routed back to the caller while every other event is still
+/// delivered through the connection.
+///
+/// Events are kept until they are read, up to 1024 of them, after which the oldest are
+/// dropped. This means a connection which is only used through subscriptions (see
+/// [`Connection::subscribe`]) doesn't have to be read.
+#[derive(Debug)]
+pub struct Connection {
+    /// Events which are not responses to a request.
+    ///
+    /// Each WebSocket is read into a queue of its own, so that the end of an old WebSocket is
+    /// never mistaken for the end of its replacement.
+    events: Arc<Queue>,
+
+    /// The background task reading from the WebSocket.
+    reader: AbortHandle,
+
+    /// The write portion of the WebSocket stream.
+    sender: Sender,
+
+    /// The Ring hosts (such as alarm base stations) reachable over the connection.
+    assets: Vec<Asset>,
+
+    /// The ID of the location the connection is to.
+    location_id: String,
+}
+
+impl Connection {
+    #[must_use]
+    pub(crate) fn new(stream: WebSocket, location_id: String, request_timeout: Duration) -> Self {
+        let (sink, stream) = stream.split();
+        let events = Arc::new(Queue::default());
+
+        let sender = Sender {
+            shared: Arc::new(Shared {
+                sink: Mutex::new(sink),
+                pending: SyncMutex::new(HashMap::new()),
+                subscribers: SyncMutex::new(Vec::new()),
+                reconnecting: AtomicBool::new(false),
+                seq: AtomicU64::new(0),
+                request_timeout,
+            }),
+        };
+
+        let reader = tokio::spawn(Self::read(
+            stream,
+            Arc::clone(&events),
+            Arc::clone(&sender.shared),
+        ))
+        .abort_handle();
+
+        Self {
+            events,
+            reader,
+            sender,
+            assets: Vec::new(),
+            location_id,
+        }
+    }
+
+    /// Swap the underlying WebSocket for a new one.
+    ///
+    /// Any [`Sender`]s created from this connection will send events using the new WebSocket.
+    /// Requests which were waiting for a response over the old WebSocket fail.
+    ///
+    /// Events which were read from the old WebSocket but are yet to be delivered are kept,
+    /// while any errors (or the end of the stream) it reported are discarded.
+    pub(super) async fn replace(&mut self, stream: WebSocket, assets: Vec<Asset>) {
+        let (sink, stream) = stream.split();
+
+        self.reader.abort();
+        self.sender.shared.pending().clear();
+
+        *self.sender.shared.sink.lock().await = sink;
+
+        let events = Arc::new(Queue::default());
+
+        for event in self.events.take().into_iter().flatten() {
+            events.push(Ok(event));
+        }
+
+        self.events = Arc::clone(&events);
+        self.reader = tokio::spawn(Self::read(stream, events, Arc::clone(&self.sender.shared)))
+            .abort_handle();
+
+        self.assets = assets;
+    }
+
+    /// Read messages from the WebSocket until it closes, routing responses to the requests
+    /// waiting for them, and everything else to the connection (and any subscribers interested
+    /// in it).
+    async fn read(mut stream: SplitStream<WebSocket>, events: Arc<Queue>, shared: Arc<Shared>) {
+        while let Some(message) = stream.next().await {
+            let Some(event) = Self::decode(message) else {
+                continue;
+            };
+
+            let waiting = event
+                .as_ref()
+                .ok()
+                .and_then(|event| shared.take_pending(&event.message));
+
+            let event = match (waiting, event) {
+                (Some(waiting), Ok(event)) => match waiting.send(event) {
+                    Ok(()) => continue,
+                    // The request has already given up, so deliver the response as an event.
+                    Err(event) => Ok(event),
+                },
+                (_, event) => event,
+            };
+
+            if let Ok(event) = &event {
+                shared.publish(event);
+            }
+
+            events.push(event);
+        }
+
+        // Dropping the waiting requests fails them, rather than leaving them to time out.
+        shared.pending().clear();
+
+        // Likewise, subscriptions end with the connection, unless it is about to be replaced.
+        let reconnecting = shared.reconnecting.load(Ordering::Relaxed);
+
+        shared
+            .subscribers()
+            .retain(|subscriber| reconnecting && !subscriber.ends_with_socket);
+
+        events.end();
+    }
+
+    /// The Ring hosts (such as alarm base stations) which can be reached over the connection.
+    #[must_use]
+    pub fn assets(&self) -> &[Asset] {
+        &self.assets
+    }
+
+    pub(super) fn set_assets(&mut self, assets: Vec<Asset>) {
+        self.assets = assets;
+    }
+
+    /// The ID of the location the connection is to.
+    pub(super) fn location_id(&self) -> &str {
+        &self.location_id
+    }
+
+    /// Keep subscriptions open when the WebSocket closes, as the connection will be replaced.
+    pub(super) fn keep_subscriptions(&self) {
+        self.sender
+            .shared
+            .reconnecting
+            .store(true, Ordering::Relaxed);
+    }
+
+    /// End every subscription, closing their receivers.
+    pub(super) fn end_subscriptions(&self) {
+        self.sender.shared.subscribers().clear();
+    }
+
+    /// How long to wait for Ring to respond to a request.
+    pub(super) fn request_timeout(&self) -> Duration {
+        self.sender.shared.request_timeout
+    }
+
+    /// Reads the next message from the stream.
+    #[must_use]
+    pub async fn next(&mut self) -> Option<Result<Event, ApiError>> {
+        StreamExt::next(self).await
+    }
+
+    /// Sends a message to Ring immediately (no buffering).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sink has already been closed.
+    pub async fn send(&mut self, event: Event) -> Result<(), ApiError> {
+        self.sender.send(event).await
+    }
+
+    /// Sends a message to Ring, and waits for the response to it.
+    ///
+    /// See [`Sender::request`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent, if the connection closes before Ring
+    /// responds, or if Ring does not respond in time.
+    pub async fn request(&self, event: Event) -> Result<Event, ApiError> {
+        self.sender.request(event).await
+    }
+
+    /// Receive the events which match a filter, in addition to them being delivered through the
+    /// connection.
+    ///
+    /// Events are routed to the receiver as soon as they are read from Ring, so the receiver
+    /// can be used from another task. The subscription lasts until the receiver is dropped, or
+    /// the connection closes (in which case the receiver closes too). The subscriptions of a
+    /// reconnecting [`crate::location::Listener`] last until it stops reconnecting.
+    #[must_use]
+    pub fn subscribe(&self, filter: Filter) -> mpsc::UnboundedReceiver<Event> {
+        self.add_subscriber(filter, false)
+    }
+
+    /// Like [`Connection::subscribe`], but the subscription also ends when the current WebSocket
+    /// closes, so that anything waiting on it learns the connection was lost straight away.
+    pub(super) fn subscribe_until_closed(&self, filter: Filter) -> mpsc::UnboundedReceiver<Event> {
+        self.add_subscriber(filter, true)
+    }
+
+    fn add_subscriber(
+        &self,
+        filter: Filter,
+        ends_with_socket: bool,
+    ) -> mpsc::UnboundedReceiver<Event> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        // Once the WebSocket has closed, the subscriber is dropped straight away (closing the
+        // receiver), unless the connection is about to be replaced.
+        let closed = self.reader.is_finished()
+            && (ends_with_socket || !self.sender.shared.reconnecting.load(Ordering::Relaxed));
+
+        if !closed {
+            self.sender.shared.subscribers().push(Subscriber {
+                matcher: filter.matcher(),
+                sender,
+                ends_with_socket,
+            });
+        }
+
+        receiver
+    }
+
+    /// Get a handle which can send events over this connection.
+    ///
+    /// The handle can be cloned and moved to other tasks, while the connection itself is used
+    /// to read events.
+    #[must_use]
+    pub fn sender(&self) -> Sender {
+        self.sender.clone()
+    }
+
+    /// Closes the connection to Ring gracefully.
+    pub async fn close(self) {
+        let closed = self.sender.shared.sink.lock().await.close().await;
+
+        if let Err(error) = closed {
+            log::error!("Error closing stream: {error:?}");
+            return;
+        }
+
+        log::info!("Shut down Websocket connection gracefully");
+    }
+
+    /// Decode a message received from Ring, or `None` if the message should be skipped.
+    fn decode(
+        message: Result<tungstenite::Message, tungstenite::Error>,
+    ) -> Option<Result<Event, ApiError>> {
+        match message {
+            Ok(tungstenite::protocol::Message::Ping(_)) => {
+                // We can safetly ignore ping messages as Tungstenite will
+                // handle the Pong response for us.
+                //
+                // https://docs.rs/tungstenite/latest/tungstenite/protocol/struct.WebSocket.html#method.write
+                log::debug!("Recieved ping message from Ring");
+
+                None
+            }
+            Ok(message) => {
+                let event = serde_json::from_str::<Event>(&message.to_string())
+                    .map_err(ApiError::InvalidResponse);
+
+                match &event {
+                    Ok(event) => log::debug!("Received event: {event:?}"),
+                    Err(error) => log::error!("Error deserializing message: {error:?}"),
+                }
+
+                Some(event)
+            }
+            Err(error) => {
+                log::error!("Error receiving message: {error:?}");
+
+                Some(Err(ApiError::WebsocketError(error)))
+            }
+        }
+    }
+}
+
+impl Stream for Connection {
+    type Item = Result<Event, ApiError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_next(cx)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.reader.abort();
+
+        // Senders may outlive the connection, so the subscribers have to be dropped explicitly.
+        self.end_subscriptions();
+    }
+}
+
+/// Events read from a WebSocket, which are waiting to be read through a [`Connection`].
+///
+/// Unlike a channel, the queue drops the oldest events once it is full, rather than growing
+/// without bound (or holding up the reader).
+#[derive(Debug, Default)]
+struct Queue {
+    state: SyncMutex<QueueState>,
+    waker: AtomicWaker,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    events: VecDeque<Result<Event, ApiError>>,
+
+    /// Whether the WebSocket has closed, so no more events will be added.
+    ended: bool,
+}
+
+impl Queue {
+    fn state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state
+            .lock()
+            .expect("Event queue should not be poisoned")
+    }
+
+    /// Add an event, dropping the oldest event if the queue is full.
+    fn push(&self, event: Result<Event, ApiError>) {
+        {
+            let mut state = self.state();
+
+            if state.events.len() >= EVENT_CAPACITY {
+                let dropped = state.events.pop_front();
+
+                log::warn!("Too many events are waiting to be read, dropping {dropped:?}");
+            }
+
+            state.events.push_back(event);
+        }
+
+        self.waker.wake();
+    }
+
+    /// Mark the end of the events, once the WebSocket has closed.
+    fn end(&self) {
+        self.state().ended = true;
+        self.waker.wake();
+    }
+
+    /// Remove every event which is yet to be read.
+    fn take(&self) -> VecDeque<Result<Event, ApiError>> {
+        std::mem::take(&mut self.state().events)
+    }
+
+    fn poll_next(&self, cx: &Context<'_>) -> Poll<Option<Result<Event, ApiError>>> {
+        if let Poll::Ready(next) = self.try_next() {
+            return Poll::Ready(next);
+        }
+
+        self.waker.register(cx.waker());
+
+        // An event may have been added before the waker was registered.
+        self.try_next()
+    }
+
+    /// The next event, or `None` once every event has been read and the WebSocket has closed.
+    fn try_next(&self) -> Poll<Option<Result<Event, ApiError>>> {
+        let mut state = self.state();
+
+        match state.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if state.ended => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// State shared between a [`Connection`] and its [`Sender`]s.
+#[derive(Debug)]
+struct Shared {
+    sink: Mutex<SplitSink<WebSocket, tungstenite::Message>>,
+    pending: Pending,
+    subscribers: Subscribers,
+
+    /// Whether subscriptions are kept when the WebSocket closes.
+    reconnecting: AtomicBool,
+
+    seq: AtomicU64,
+    request_timeout: Duration,
+}
+
+impl Shared {
+    fn pending(&self) -> std::sync::MutexGuard<'_, HashMap<u64, PendingRequest>> {
+        self.pending
+            .lock()
+            .expect("Pending requests should not be poisoned")
+    }
+
+    /// Take the request waiting for a message, if the message is its response. Responses have
+    /// the same sequence number as the request, and the same name.
+    fn take_pending(&self, message: &Message) -> Option<oneshot::Sender<Event>> {
+        let seq = message.seq()?;
+        let name = message.name();
+
+        match self.pending().entry(seq) {
+            Entry::Occupied(request) if request.get().name == name => {
+                Some(request.remove().respond)
+            }
+            _ => None,
+        }
+    }
+
+    fn subscribers(&self) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .expect("Subscribers should not be poisoned")
+    }
+
+    /// Deliver an event to every subscriber interested in it, forgetting any subscribers which
+    /// have gone away (whether or not they were interested).
+    fn publish(&self, event: &Event) {
+        self.subscribers().retain_mut(|subscriber| {
+            !subscriber.sender.is_closed()
+                && (!subscriber.matcher.matches(event)
+                    || subscriber.sender.send(event.clone()).is_ok())
+        });
+    }
+}
+
+/// A cloneable handle for sending events to Ring over a [`Connection`].
+///
+/// Senders obtained from a reconnecting [`crate::location::Listener`] keep working after the
+/// listener has reconnected.
+#[derive(Debug, Clone)]
+pub struct Sender {
+    shared: Arc<Shared>,
+}
+
+impl Sender {
+    /// Sends a message to Ring immediately (no buffering).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sink has already been closed.
+    pub async fn send(&self, event: Event) -> Result<(), ApiError> {
+        self.send_message(event.try_into()?).await
+    }
+
+    /// Sends a message to Ring, and waits for the response to it.
+    ///
+    /// The message is tagged with a unique sequence number (`seq`), which Ring includes in its
+    /// response (a message with the same name as the request). Any other events received in the
+    /// meantime are still delivered through the [`Connection`].
+    ///
+    /// The request times out after the duration configured using
+    /// [`crate::ClientBuilder::request_timeout`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message could not be sent, if the connection closes before Ring
+    /// responds, or if Ring does not respond in time.
+    pub async fn request(&self, event: Event) -> Result<Event, ApiError> {
+        let seq = self.shared.seq.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut message = serde_json::to_value(&event)?;
+        let name = event.message.name().to_string();
+
+        if let Some(body) = message["msg"].as_object_mut() {
+            body.insert("seq".to_string(), seq.into());
+        }
+
+        let (respond, response) = oneshot::channel();
+
+        self.shared
+            .pending()
+            .insert(seq, PendingRequest { name, respond });
+
+        let sent = self
+            .send_message(tungstenite::Message::Text(Utf8Bytes::from(
+                &message.to_string(),
+            )))
+            .await;
+
+        if let Err(error) = sent {
+            self.shared.pending().remove(&seq);
+            return Err(error);
+        }
+
+        match tokio::time::timeout(self.shared.request_timeout, response).await {
+            Ok(Ok(event)) => Ok(event),
+            Ok(Err(_)) => Err(ApiError::ConnectionClosed),
+            Err(_) => {
+                self.shared.pending().remove(&seq);
+
+                Err(ApiError::RequestTimedOut)
+            }
+        }
+    }
+
+    async fn send_message(&self, message: tungstenite::Message) -> Result<(), ApiError> {
+        self.shared
+            .sink
+            .lock()
+            .await
+            .send(message)
+            .await
+            .map_err(ApiError::WebsocketError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::Message;
+    use serde_json::json;
+
+    fn update(index: usize) -> Event {
+        Event::new(Message::DataUpdate(json!({ "index": index })))
+    }
+
+    #[tokio::test]
+    async fn test_dropping_the_oldest_events_once_full() {
+        let queue = Queue::default();
+
+        for index in 0..=EVENT_CAPACITY {
+            queue.push(Ok(update(index)));
+        }
+
+        queue.end();
+
+        let mut events = Vec::new();
+
+        while let Some(event) = std::future::poll_fn(|cx| queue.poll_next(cx)).await {
+            events.push(event.expect("Only updates were queued"));
+        }
+
+        assert_eq!(events.len(), EVENT_CAPACITY);
+        assert_eq!(
+            events.first().map(|event| &event.message),
+            Some(&Message::DataUpdate(json!({ "index": 1 })))
+        );
+    }
+}
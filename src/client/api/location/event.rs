@@ -1,20 +1,23 @@
+use crate::ApiError;
 use crate::helper::url::Url;
-use crate::location::Location;
-use crate::{ApiError, helper};
-use futures_util::stream::SplitStream;
-use futures_util::{SinkExt, StreamExt, stream::SplitSink};
-use serde::{Deserialize, Serialize};
+use crate::location::connection::WebSocket;
+use crate::location::{Connection, Filter, Location, Sender};
+use crate::ticket::Asset;
+use futures_util::Stream;
+use serde::de::Error as _;
+use serde::ser::{Error as _, SerializeMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::PartialEq;
 use std::future::Future;
 use std::sync::Arc;
-use tokio::net::TcpStream;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc};
 use tokio_tungstenite::tungstenite::Utf8Bytes;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite};
+use tokio_tungstenite::{connect_async, tungstenite};
 
 /// A real-time event which occured in a Location.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Event {
     /// The content of the event.
     #[serde(rename = "msg")]
@@ -29,9 +32,36 @@ impl Event {
     }
 }
 
+/// Something reported by a [`Listener`]: either an event sent by Ring, or a change to the
+/// connection to Ring.
+///
+/// Changes to the connection are never sent by Ring, and are only reported by listeners which
+/// reconnect automatically (see [`Listener::reconnecting`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListenerEvent {
+    /// An event sent by Ring.
+    Event(Event),
+
+    /// The connection to Ring was lost.
+    Disconnected,
+
+    /// The connection to Ring was re-established after being lost, and any subscriptions have
+    /// been sent again.
+    Reconnected,
+}
+
+impl From<Event> for ListenerEvent {
+    fn from(event: Event) -> Self {
+        Self::Event(event)
+    }
+}
+
 /// A message sent to or from Ring via WebSocket.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(tag = "msg")]
+///
+/// The message kind is sent by Ring as the `msg` field, and the rest of the message is kept as
+/// the payload of the variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(missing_docs)]
 #[non_exhaustive]
 pub enum Message {
@@ -43,136 +73,299 @@ pub enum Message {
 
     DataUpdate(serde_json::Value),
 
-    /// A message which is yet to be mapped by the crate.
-    #[serde(other)]
-    Unknown,
-}
+    /// The list of devices connected to an alarm base station.
+    ///
+    /// See [`crate::location::Location::get_alarm_devices`].
+    DeviceInfoDocGetList(serde_json::Value),
 
-impl TryFrom<Event> for tungstenite::protocol::Message {
-    type Error = serde_json::Error;
+    /// The list of every device in the location, across all of its assets.
+    DeviceInfoDocGetAllList(serde_json::Value),
 
-    fn try_from(event: Event) -> Result<Self, Self::Error> {
-        Ok(Self::Text(Utf8Bytes::from(&serde_json::to_string(&event)?)))
-    }
-}
+    /// The list of rooms configured in the location.
+    RoomGetList(serde_json::Value),
 
-/// A live connection for exchanging messages with Ring.
-///
-/// For example, to enable an Alarm system.
-#[derive(Debug)]
-pub struct Connection {
-    /// The read portion of the WebSocket stream.
-    stream: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    /// A message passed through from a device without being interpreted by Ring (such as an
+    /// alarm entry or exit delay counting down).
+    Passthru(serde_json::Value),
 
-    /// The write portion of the WebSocket stream.
-    sink: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
+    /// A message which is yet to be mapped by the crate.
+    Unknown {
+        /// The kind of message, as sent by Ring.
+        msg: String,
+
+        /// The rest of the message.
+        body: serde_json::Value,
+    },
 }
 
-impl Connection {
+impl Message {
+    /// The sequence number Ring uses to match a response to the request it answers, if the
+    /// message has one.
+    ///
+    /// See [`Connection::request`].
     #[must_use]
-    pub(crate) fn new(stream: WebSocketStream<MaybeTlsStream<TcpStream>>) -> Self {
-        let (sink, stream) = stream.split();
+    pub fn seq(&self) -> Option<u64> {
+        self.payload()["seq"].as_u64()
+    }
 
-        Self { stream, sink }
+    /// The content of the message (everything other than its name).
+    pub(crate) const fn payload(&self) -> &serde_json::Value {
+        match self {
+            Self::SubscriptionTopicsInfo(payload)
+            | Self::DeviceInfoSet(payload)
+            | Self::SessionInfo(payload)
+            | Self::DataUpdate(payload)
+            | Self::DeviceInfoDocGetList(payload)
+            | Self::DeviceInfoDocGetAllList(payload)
+            | Self::RoomGetList(payload)
+            | Self::Passthru(payload)
+            | Self::Unknown { body: payload, .. } => payload,
+        }
     }
 
-    /// Reads the next message from the stream.
+    /// The name of the message, as sent by Ring (i.e. its `msg` field).
     #[must_use]
-    pub async fn next(&mut self) -> Option<Result<Event, ApiError>> {
-        while let Some(message) = self.stream.next().await {
-            match message {
-                Ok(message) => {
-                    if let tungstenite::protocol::Message::Ping(_) = message {
-                        // We can safetly ignore ping messages as Tungstenite will
-                        // handle the Pong response for us.
-                        //
-                        // https://docs.rs/tungstenite/latest/tungstenite/protocol/struct.WebSocket.html#method.write
-                        log::debug!("Recieved ping message from Ring");
-
-                        continue;
-                    }
-
-                    let event = serde_json::from_str::<Event>(&message.to_string())
-                        .map_err(ApiError::InvalidResponse);
-
-                    if let Err(error) = event {
-                        log::error!("Error deserializing message: {error:?}");
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SubscriptionTopicsInfo(_) => "SubscriptionTopicsInfo",
+            Self::DeviceInfoSet(_) => "DeviceInfoSet",
+            Self::SessionInfo(_) => "SessionInfo",
+            Self::DataUpdate(_) => "DataUpdate",
+            Self::DeviceInfoDocGetList(_) => "DeviceInfoDocGetList",
+            Self::DeviceInfoDocGetAllList(_) => "DeviceInfoDocGetAllList",
+            Self::RoomGetList(_) => "RoomGetList",
+            Self::Passthru(_) => "Passthru",
+            Self::Unknown { msg, .. } => msg,
+        }
+    }
+}
 
-                        return Some(Err(error));
-                    }
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = self.name();
+
+        let fields = match self.payload() {
+            serde_json::Value::Object(fields) => Some(fields),
+            serde_json::Value::Null => None,
+            _ => {
+                return Err(S::Error::custom(format!(
+                    "The payload of a {name} message must be an object"
+                )));
+            }
+        };
 
-                    log::debug!("Received event: {event:?}");
+        let mut map = serializer.serialize_map(None)?;
 
-                    return Some(event);
-                }
-                Err(error) => {
-                    log::error!("Error receiving message: {error:?}");
+        map.serialize_entry("msg", name)?;
 
-                    return Some(Err(ApiError::WebsocketError(error)));
-                }
+        for (key, value) in fields.into_iter().flatten() {
+            if key != "msg" {
+                map.serialize_entry(key, value)?;
             }
         }
 
-        None
+        map.end()
     }
+}
 
-    /// Sends a message to Ring immediately (no buffering).
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the sink has already been closed.
-    pub async fn send(&mut self, event: Event) -> Result<(), ApiError> {
-        self.sink
-            .send(event.try_into()?)
-            .await
-            .map_err(ApiError::WebsocketError)
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = serde_json::Map::deserialize(deserializer)?;
+
+        let msg = match fields.remove("msg") {
+            Some(serde_json::Value::String(msg)) => msg,
+            Some(_) => return Err(D::Error::custom("The msg field must be a string")),
+            None => return Err(D::Error::missing_field("msg")),
+        };
+
+        let payload = serde_json::Value::Object(fields);
+
+        Ok(match msg.as_str() {
+            "SubscriptionTopicsInfo" => Self::SubscriptionTopicsInfo(payload),
+            "DeviceInfoSet" => Self::DeviceInfoSet(payload),
+            "SessionInfo" => Self::SessionInfo(payload),
+            "DataUpdate" => Self::DataUpdate(payload),
+            "DeviceInfoDocGetList" => Self::DeviceInfoDocGetList(payload),
+            "DeviceInfoDocGetAllList" => Self::DeviceInfoDocGetAllList(payload),
+            "RoomGetList" => Self::RoomGetList(payload),
+            "Passthru" => Self::Passthru(payload),
+            _ => Self::Unknown { msg, body: payload },
+        })
     }
+}
 
-    /// Closes the connection to Ring gracefully.
-    pub async fn close(self) {
-        let stream = self.stream.reunite(self.sink);
+impl TryFrom<Event> for tungstenite::protocol::Message {
+    type Error = serde_json::Error;
 
-        match stream {
-            Ok(mut stream) => {
-                let closed = stream.close(None).await;
+    fn try_from(event: Event) -> Result<Self, Self::Error> {
+        Ok(Self::Text(Utf8Bytes::from(&serde_json::to_string(&event)?)))
+    }
+}
 
-                if let Err(error) = closed {
-                    log::error!("Error closing stream: {error:?}");
-                    return;
-                }
+/// Controls how a [`Listener`] reconnects to Ring after the connection is lost.
+///
+/// The delay between attempts starts at the initial delay, and doubles after each failed
+/// attempt up to the maximum delay.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: Option<u32>,
+}
 
-                log::info!("Shut down Websocket connection gracefully");
-            }
-            Err(_) => {
-                log::info!("Unable to reunite write and read pair into stream");
-            }
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_attempts: None,
         }
     }
 }
 
+impl ReconnectPolicy {
+    /// The delay before the first attempt to reconnect.
+    ///
+    /// Defaults to one second.
+    #[must_use]
+    pub const fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// The longest delay between attempts to reconnect.
+    ///
+    /// Defaults to one minute.
+    #[must_use]
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The number of consecutive failed attempts after which the listener gives up and stops.
+    ///
+    /// By default, the listener tries to reconnect forever.
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// The delay before the given (one-based) attempt to reconnect.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
 /// An event listener for a Location.
 #[derive(Debug)]
-pub struct Listener<'a> {
-    location: &'a Location<'a>,
+pub struct Listener {
+    location: Location,
     connection: Connection,
+
+    /// Events which are sent to Ring again whenever the listener reconnects.
+    subscriptions: Vec<Event>,
+
+    /// How to reconnect after the connection is lost, if at all.
+    reconnect: Option<ReconnectPolicy>,
+
+    /// Whether the connection was lost, and should be re-established before reading the next
+    /// event.
+    reconnect_pending: bool,
 }
 
-impl<'a> Listener<'a> {
+impl Listener {
     /// Create a brand new event listener for a location.
     ///
     /// This generally accepts a callback defined by the caller, which is triggered whenever an
     /// event is triggered by Ring.
     #[must_use]
-    pub fn new<'b>(
-        location: &'b Location<'_>,
-        stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
-    ) -> Listener<'b> {
-        Listener {
+    pub fn new(location: Location, stream: WebSocket) -> Self {
+        Self {
+            connection: Connection::new(
+                stream,
+                location.id().to_string(),
+                location.session.inner.request_timeout,
+            ),
             location,
-            connection: Connection::new(stream),
+            subscriptions: Vec::new(),
+            reconnect: None,
+            reconnect_pending: false,
         }
     }
 
+    /// Reconnect to Ring automatically whenever the connection is lost, instead of stopping.
+    ///
+    /// A fresh ticket is generated for every attempt, and any subscriptions (see
+    /// [`Listener::add_subscription`]) are sent again once reconnected. The event handler is
+    /// notified using [`ListenerEvent::Disconnected`] when the connection is lost, and
+    /// [`ListenerEvent::Reconnected`] once it has been re-established.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::location::{ListenerEvent, ReconnectPolicy};
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let locations = client.get_locations()
+    ///      .await
+    ///      .expect("Getting locations should not fail");
+    ///
+    /// let location = locations
+    ///      .first()
+    ///      .expect("There should be at least one location");
+    ///
+    /// let mut listener = location.get_listener()
+    ///      .await
+    ///      .expect("Creating a listener should not fail")
+    ///      .reconnecting(ReconnectPolicy::default());
+    ///
+    /// listener.listen::<_, _, ()>(|event, _, _| async move {
+    ///     match event {
+    ///         ListenerEvent::Disconnected => println!("Lost connection to Ring, reconnecting"),
+    ///         ListenerEvent::Reconnected => println!("Reconnected to Ring"),
+    ///         event => println!("New event: {:#?}", event),
+    ///     }
+    ///
+    ///     Ok(true)
+    /// })
+    /// .await;
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn reconnecting(mut self, policy: ReconnectPolicy) -> Self {
+        self.connection.keep_subscriptions();
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Send an event to Ring, and send it again every time the listener reconnects.
+    ///
+    /// This is useful for events which subscribe to updates, as subscriptions do not survive
+    /// the connection being lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection is closed.
+    pub async fn add_subscription(&mut self, event: Event) -> Result<(), ApiError> {
+        self.connection.send(event.clone()).await?;
+        self.subscriptions.push(event);
+
+        Ok(())
+    }
+
     /// Listen for events in a particular location.
     ///
     /// # Example
@@ -224,18 +417,22 @@ impl<'a> Listener<'a> {
     /// # });
     ///```
     ///
+    /// By default, the listener stops once the connection to Ring is closed. See
+    /// [`Listener::reconnecting`] to reconnect automatically instead.
+    ///
     /// # Errors
     ///
     /// Returns the error from the event handler if it returns an error when called.
-    pub async fn listen<EventHandler, EventHandlerFut, E>(
+    pub async fn listen<'a, EventHandler, EventHandlerFut, E>(
         &'a mut self,
         on_event: EventHandler,
     ) -> Result<(), E>
     where
         EventHandler:
-            Fn(Event, &'a Location<'a>, Arc<Mutex<&'a mut Connection>>) -> EventHandlerFut,
+            Fn(ListenerEvent, &'a Location, Arc<Mutex<&'a mut Connection>>) -> EventHandlerFut,
         EventHandlerFut: Future<Output = Result<bool, E>>,
     {
+        let location = &self.location;
         let connection = Arc::new(Mutex::new(&mut self.connection));
 
         loop {
@@ -243,35 +440,252 @@ impl<'a> Listener<'a> {
             // to allow any on_event calls to use the connection without blocking
             let event = { connection.lock().await.next().await };
 
-            match event {
+            let lost_connection = match event {
                 Some(Ok(event)) => {
-                    if event.message == Message::Unknown {
-                        log::warn!("Unknown message received: {:?}", event.message);
-                        continue;
-                    }
-
                     log::debug!("Received event: {event:?}");
 
-                    let outcome = on_event(event, self.location, Arc::clone(&connection)).await?;
+                    let outcome = on_event(event.into(), location, Arc::clone(&connection)).await?;
 
                     if !outcome {
                         log::debug!("Event handler returned false, stopping listener");
                         break;
                     }
+
+                    false
                 }
                 Some(Err(error)) => {
                     log::error!("Error receiving event: {error:?}");
+
+                    self.reconnect.is_some() && matches!(error, ApiError::WebsocketError(_))
                 }
                 None => {
-                    log::info!("Websocket stream closed, stopping listener");
+                    log::info!("Websocket stream closed");
+
+                    true
+                }
+            };
+
+            let policy = match &self.reconnect {
+                Some(policy) if lost_connection => policy,
+                None if lost_connection => {
+                    log::info!("Stopping listener");
                     break;
                 }
+                _ => continue,
+            };
+
+            log::warn!("Lost connection to Ring, reconnecting");
+
+            let outcome = on_event(
+                ListenerEvent::Disconnected,
+                location,
+                Arc::clone(&connection),
+            )
+            .await?;
+
+            if !outcome {
+                log::debug!("Event handler returned false, stopping listener");
+                break;
+            }
+
+            let reconnected = Self::reconnect(
+                location,
+                policy,
+                &self.subscriptions,
+                *connection.lock().await,
+            )
+            .await;
+
+            if !reconnected {
+                log::error!("Unable to reconnect to Ring, stopping listener");
+                break;
+            }
+
+            let outcome = on_event(
+                ListenerEvent::Reconnected,
+                location,
+                Arc::clone(&connection),
+            )
+            .await?;
+
+            if !outcome {
+                log::debug!("Event handler returned false, stopping listener");
+                break;
             }
         }
 
         Ok(())
     }
 
+    /// Read the next event from Ring.
+    ///
+    /// Unlike [`Connection::next`], reconnecting listeners (see [`Listener::reconnecting`])
+    /// report [`ListenerEvent::Disconnected`] and [`ListenerEvent::Reconnected`] rather than
+    /// the stream ending.
+    ///
+    /// Returns `None` once the listener has stopped.
+    pub async fn next(&mut self) -> Option<Result<ListenerEvent, ApiError>> {
+        if std::mem::take(&mut self.reconnect_pending) {
+            let policy = self.reconnect.as_ref()?;
+
+            let reconnected = Self::reconnect(
+                &self.location,
+                policy,
+                &self.subscriptions,
+                &mut self.connection,
+            )
+            .await;
+
+            if !reconnected {
+                log::error!("Unable to reconnect to Ring, stopping listener");
+                return None;
+            }
+
+            return Some(Ok(ListenerEvent::Reconnected));
+        }
+
+        match self.connection.next().await {
+            Some(Err(error @ ApiError::WebsocketError(_))) if self.reconnect.is_some() => {
+                log::error!("Error receiving event: {error:?}");
+            }
+            None if self.reconnect.is_some() => {
+                log::info!("Websocket stream closed");
+            }
+            event => return event.map(|event| event.map(ListenerEvent::Event)),
+        }
+
+        log::warn!("Lost connection to Ring, reconnecting");
+
+        self.reconnect_pending = true;
+
+        Some(Ok(ListenerEvent::Disconnected))
+    }
+
+    /// Turn the listener into a [`Stream`] of events, for use with
+    /// [`StreamExt`](futures_util::StreamExt) combinators, `tokio::select!`, or merging with the
+    /// events from other locations.
+    ///
+    /// The stream behaves in the same way as [`Listener::next`]. Use [`Listener::sender`]
+    /// beforehand to send events to Ring while the stream is being consumed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::StreamExt;
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let locations = client.get_locations()
+    ///      .await
+    ///      .expect("Getting locations should not fail");
+    ///
+    /// let mut listeners = Vec::new();
+    ///
+    /// for location in &locations {
+    ///     let listener = location.get_listener()
+    ///          .await
+    ///          .expect("Creating a listener should not fail");
+    ///
+    ///     listeners.push(listener.into_stream().boxed());
+    /// }
+    ///
+    /// // Handle the events from every location as they arrive.
+    /// let mut events = futures_util::stream::select_all(listeners);
+    ///
+    /// while let Some(event) = events.next().await {
+    ///     println!("New event: {:#?}", event);
+    /// }
+    /// # });
+    /// ```
+    pub fn into_stream(
+        self,
+    ) -> impl Stream<Item = Result<ListenerEvent, ApiError>> + Send + 'static {
+        futures_util::stream::unfold(self, |mut listener| async move {
+            listener.next().await.map(|event| (event, listener))
+        })
+    }
+
+    /// The location being listened to, and the connection to it.
+    pub(super) const fn parts(&mut self) -> (&Location, &mut Connection) {
+        (&self.location, &mut self.connection)
+    }
+
+    /// Get a handle which can send events to Ring, independently of the listener.
+    ///
+    /// The handle keeps working after a reconnecting listener re-establishes its connection.
+    #[must_use]
+    pub fn sender(&self) -> Sender {
+        self.connection.sender()
+    }
+
+    /// Open a new connection to the location, backing off between failed attempts, and send
+    /// the subscriptions again.
+    ///
+    /// Returns `false` if the policy's maximum number of attempts is exhausted.
+    async fn reconnect(
+        location: &Location,
+        policy: &ReconnectPolicy,
+        subscriptions: &[Event],
+        connection: &mut Connection,
+    ) -> bool {
+        let mut attempt = 1;
+
+        loop {
+            if policy.max_attempts.is_some_and(|max| attempt > max) {
+                // The listener is stopping, so subscribers won't receive anything else.
+                connection.end_subscriptions();
+
+                return false;
+            }
+
+            let delay = policy.delay(attempt);
+
+            log::debug!("Reconnecting to Ring in {delay:?} (attempt {attempt})");
+
+            tokio::time::sleep(delay).await;
+
+            attempt += 1;
+
+            match location.connect().await {
+                Ok((stream, assets)) => connection.replace(stream, assets).await,
+                Err(error) => {
+                    log::error!("Failed to reconnect to Ring: {error:?}");
+                    continue;
+                }
+            }
+
+            let mut replayed = Ok(());
+
+            for subscription in subscriptions {
+                replayed = connection.send(subscription.clone()).await;
+
+                if replayed.is_err() {
+                    break;
+                }
+            }
+
+            match replayed {
+                Ok(()) => {
+                    log::info!("Reconnected to Ring");
+
+                    return true;
+                }
+                Err(error) => {
+                    log::error!("Failed to resubscribe after reconnecting to Ring: {error:?}");
+                }
+            }
+        }
+    }
+
     /// Send an event to Ring.
     ///
     /// # Errors
@@ -327,42 +741,169 @@ impl<'a> Listener<'a> {
         self.connection.send(event).await
     }
 
+    /// Receive the events which match a filter (such as a sensor being faulted), without
+    /// having to pick them out of every event Ring sends.
+    ///
+    /// The listener still receives every event, and subscriptions survive the listener
+    /// reconnecting. The listener doesn't have to be read as well, as only the most recent
+    /// events are kept for it (see [`Connection`]). See [`Connection::subscribe`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::location::{EventKind, Filter, Zid};
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let locations = client.get_locations()
+    ///      .await
+    ///      .expect("Getting locations should not fail");
+    ///
+    /// let location = locations
+    ///      .first()
+    ///      .expect("There should be at least one location");
+    ///
+    /// let listener = location.get_listener()
+    ///      .await
+    ///      .expect("Creating a listener should not fail");
+    ///
+    /// let mut front_door = listener.subscribe(
+    ///     Filter::device(Zid::new("front-door-zid")).kind(EventKind::Faulted)
+    /// );
+    ///
+    /// while let Some(event) = front_door.recv().await {
+    ///     println!("The front door was opened: {:#?}", event);
+    /// }
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn subscribe(&self, filter: Filter) -> mpsc::UnboundedReceiver<Event> {
+        self.connection.subscribe(filter)
+    }
+
+    /// Send an event to Ring, and wait for the response to it.
+    ///
+    /// Any other events received while waiting are still delivered by the listener. See
+    /// [`Sender::request`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event could not be sent, if the connection closes before Ring
+    /// responds, or if Ring does not respond in time.
+    pub async fn request(&self, event: Event) -> Result<Event, ApiError> {
+        self.connection.request(event).await
+    }
+
     /// Close the underlying connection to Ring.
     pub async fn close(self) {
         self.connection.close().await;
     }
 }
 
-impl<'a> Location<'a> {
+impl Location {
     /// Get a listener for events in a location.
     ///
     /// # Errors
     ///
     /// Will return an error if a connection cannot be established with Ring.
-    pub async fn get_listener(&'a self) -> Result<Listener<'a>, ApiError> {
-        let (stream, _) = self.connect().await?;
+    pub async fn get_listener(&self) -> Result<Listener, ApiError> {
+        let (stream, assets) = self.connect().await?;
 
-        Ok(Listener::new(self, stream))
+        let mut listener = Listener::new(self.clone(), stream);
+        listener.connection.set_assets(assets);
+
+        Ok(listener)
     }
 
     /// Generate a ticket (credentials and URI for a Ring Websocket server) and connect to it.
-    async fn connect(
-        &self,
-    ) -> Result<
-        (
-            WebSocketStream<MaybeTlsStream<TcpStream>>,
-            tungstenite::handshake::client::Response,
-        ),
-        ApiError,
-    > {
+    ///
+    /// Returns the connected stream, along with the assets which can be reached over it.
+    async fn connect(&self) -> Result<(WebSocket, Vec<Asset>), ApiError> {
         let ticket = self.session.get_ticket(&self.data.id).await?;
 
-        let request = helper::url::get_base_url(&Url::Websocket {
-            host: &ticket.host,
-            auth_code: &ticket.id,
-        })
-        .into_client_request()?;
+        let request = self
+            .session
+            .inner
+            .api
+            .endpoints
+            .get_base_url(&Url::Websocket {
+                host: &ticket.host,
+                auth_code: &ticket.id,
+            })
+            .into_client_request()?;
+
+        let (stream, _) = connect_async(request).await?;
+
+        Ok((stream, ticket.assets))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decoding_known_messages() {
+        let event = serde_json::from_value::<Event>(json!({
+            "channel": "message",
+            "msg": { "msg": "RoomGetList", "seq": 3, "body": [] }
+        }))
+        .expect("Event should be decoded");
+
+        assert_eq!(
+            event.message,
+            Message::RoomGetList(json!({ "seq": 3, "body": [] }))
+        );
+        assert_eq!(event.message.seq(), Some(3));
+    }
+
+    #[test]
+    fn test_keeping_unknown_messages() {
+        let message = serde_json::from_value::<Message>(json!({
+            "msg": "SomethingNew",
+            "datatype": "SomethingNewType",
+            "body": [{ "zid": "mock-zid" }]
+        }))
+        .expect("Message should be decoded");
+
+        assert_eq!(
+            message,
+            Message::Unknown {
+                msg: "SomethingNew".to_string(),
+                body: json!({
+                    "datatype": "SomethingNewType",
+                    "body": [{ "zid": "mock-zid" }]
+                }),
+            }
+        );
+
+        assert_eq!(
+            serde_json::to_value(&message).expect("Message should be encoded"),
+            json!({
+                "msg": "SomethingNew",
+                "datatype": "SomethingNewType",
+                "body": [{ "zid": "mock-zid" }]
+            })
+        );
+    }
 
-        Ok(connect_async(request).await?)
+    #[test]
+    fn test_not_encoding_payloads_which_are_not_objects() {
+        assert!(serde_json::to_value(Message::DataUpdate(json!([]))).is_err());
+        assert_eq!(
+            serde_json::to_value(Message::Passthru(serde_json::Value::Null))
+                .expect("Message should be encoded"),
+            json!({ "msg": "Passthru" })
+        );
     }
 }
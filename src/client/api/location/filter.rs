@@ -0,0 +1,385 @@
+use crate::location::{Event, Zid};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// What happened to an alarm device, as reported by a device document in an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// Any update to the device.
+    Updated,
+
+    /// A sensor was faulted (i.e. a door was opened, or motion was detected).
+    Faulted,
+
+    /// A sensor is no longer faulted (i.e. a door was closed).
+    FaultCleared,
+
+    /// The mode of a security panel changed (i.e. the alarm was armed or disarmed).
+    ModeChanged,
+
+    /// The casing of the device was opened.
+    Tampered,
+
+    /// The device can no longer communicate with the base station.
+    CommFailure,
+}
+
+impl EventKind {
+    /// Whether a device document describes this kind of event.
+    ///
+    /// The raw document is used, as the updates Ring pushes only carry the fields which
+    /// changed (so usually lack the type of the device).
+    fn matches(self, document: &Value, changes: Changes) -> bool {
+        let general = &document["general"]["v2"];
+        let device = &document["device"]["v1"];
+
+        match self {
+            Self::Updated => true,
+            Self::Faulted => changes.fault == Some((true, true)),
+            Self::FaultCleared => changes.fault == Some((false, true)),
+            Self::ModeChanged => {
+                device.get("mode").is_some()
+                    && general
+                        .get("deviceType")
+                        .is_none_or(|device_type| device_type == "security-panel")
+            }
+            Self::Tampered => changes.tamper == Some((true, true)),
+            Self::CommFailure => general["commStatus"] == "error",
+        }
+    }
+}
+
+/// Selects the events a subscriber is interested in.
+///
+/// Each criterion which is set must match, and an event matches a criterion if it matches any
+/// of the values given for it. For example, the following matches events where either sensor
+/// is faulted:
+///
+/// ```
+/// use ring_client::location::{EventKind, Filter, Zid};
+///
+/// let filter = Filter::devices([Zid::new("front-door"), Zid::new("back-door")])
+///     .kind(EventKind::Faulted);
+/// ```
+///
+/// Device and kind criteria are matched against the device documents in an event (such as a
+/// `DataUpdate` with the `DeviceInfoDocType` datatype), so events without any devices (such
+/// as session info, or heartbeats) never match them.
+///
+/// [`EventKind::Faulted`], [`EventKind::FaultCleared`] and [`EventKind::Tampered`] match
+/// changes: each subscription remembers the last state it saw for each device, so a document
+/// which repeats the current state doesn't match again.
+///
+/// See [`crate::location::Listener::subscribe`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    devices: Vec<Zid>,
+    kinds: Vec<EventKind>,
+    datatypes: Vec<String>,
+}
+
+impl Filter {
+    /// A filter which matches every event.
+    #[must_use]
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// A filter which matches events about the given device.
+    #[must_use]
+    pub fn device(zid: Zid) -> Self {
+        Self::devices([zid])
+    }
+
+    /// A filter which matches events about any of the given devices.
+    #[must_use]
+    pub fn devices(zids: impl IntoIterator<Item = Zid>) -> Self {
+        Self {
+            devices: zids.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Only match events of the given kind (or any of the kinds, if called more than once).
+    #[must_use]
+    pub fn kind(mut self, kind: EventKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    /// Only match messages with the given `datatype` (or any of the datatypes, if called more
+    /// than once), such as `DeviceInfoDocType`.
+    #[must_use]
+    pub fn datatype(mut self, datatype: impl Into<String>) -> Self {
+        self.datatypes.push(datatype.into());
+        self
+    }
+
+    /// Start matching events against the filter, remembering the state of each device seen.
+    pub(super) fn matcher(self) -> Matcher {
+        Matcher {
+            filter: self,
+            devices: HashMap::new(),
+        }
+    }
+}
+
+/// What a device document reported about its device, if anything, along with whether it
+/// differs from the last state seen for the device.
+#[derive(Debug, Clone, Copy, Default)]
+struct Changes {
+    fault: Option<(bool, bool)>,
+    tamper: Option<(bool, bool)>,
+}
+
+/// The last state seen for a device.
+#[derive(Debug, Default)]
+struct DeviceState {
+    faulted: Option<bool>,
+    tampered: Option<bool>,
+}
+
+/// Matches events against a [`Filter`] for a single subscriber, tracking the state of each
+/// device so that changes can be matched.
+#[derive(Debug)]
+pub(super) struct Matcher {
+    filter: Filter,
+    devices: HashMap<String, DeviceState>,
+}
+
+impl Matcher {
+    /// Whether the event is selected by the filter.
+    pub(super) fn matches(&mut self, event: &Event) -> bool {
+        let payload = event.message.payload();
+        let filter = &self.filter;
+
+        if !filter.datatypes.is_empty()
+            && !filter
+                .datatypes
+                .iter()
+                .any(|datatype| payload["datatype"] == datatype.as_str())
+        {
+            return false;
+        }
+
+        let documents = payload["body"].as_array().map_or(&[][..], Vec::as_slice);
+
+        if filter.kinds.is_empty() {
+            // Matching on devices alone doesn't need a full device document, as some messages
+            // only carry the ID of the device they affected.
+            return filter.devices.is_empty()
+                || filter.devices.iter().any(|zid| {
+                    payload["context"]["affectedEntityId"] == zid.as_str()
+                        || documents.iter().any(|document| {
+                            document["general"]["v2"]["zid"] == zid.as_str()
+                                || document["zid"] == zid.as_str()
+                        })
+                });
+        }
+
+        documents.iter().fold(false, |matched, document| {
+            let zid = document["general"]["v2"]["zid"]
+                .as_str()
+                .or_else(|| document["zid"].as_str());
+
+            // Every document is checked (rather than stopping at the first match), so that the
+            // last state seen for each device stays up to date.
+            let changes = zid.map_or_else(Changes::default, |zid| self.track(zid, document));
+            let filter = &self.filter;
+
+            matched
+                || (zid.is_some_and(|zid| {
+                    filter.devices.is_empty()
+                        || filter.devices.iter().any(|device| device.as_str() == zid)
+                }) && filter
+                    .kinds
+                    .iter()
+                    .any(|kind| kind.matches(document, changes)))
+        })
+    }
+
+    /// Record the state reported by a document, returning it along with whether it changed.
+    fn track(&mut self, zid: &str, document: &Value) -> Changes {
+        fn record(last: &mut Option<bool>, value: bool) -> (bool, bool) {
+            (value, last.replace(value) != Some(value))
+        }
+
+        let faulted = document["device"]["v1"]["faulted"].as_bool();
+        let tampered = document["general"]["v2"]["tamperStatus"]
+            .as_str()
+            .map(|status| status == "tamper");
+
+        if faulted.is_none() && tampered.is_none() {
+            return Changes::default();
+        }
+
+        let state = self.devices.entry(zid.to_string()).or_default();
+
+        Changes {
+            fault: faulted.map(|faulted| record(&mut state.faulted, faulted)),
+            tamper: tampered.map(|tampered| record(&mut state.tampered, tampered)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::Message;
+    use serde_json::json;
+
+    fn update(zid: &str, device_type: &str, device: &Value) -> Event {
+        Event::new(Message::DataUpdate(json!({
+            "datatype": "DeviceInfoDocType",
+            "body": [{
+                "general": { "v2": { "zid": zid, "deviceType": device_type } },
+                "device": { "v1": device }
+            }]
+        })))
+    }
+
+    #[test]
+    fn test_filtering_by_device_and_kind() {
+        let mut matcher = Filter::device(Zid::new("mock-zid-1"))
+            .kind(EventKind::Faulted)
+            .matcher();
+
+        assert!(matcher.matches(&update(
+            "mock-zid-1",
+            "sensor.contact",
+            &json!({ "faulted": true })
+        )));
+        assert!(!matcher.matches(&update(
+            "mock-zid-1",
+            "sensor.contact",
+            &json!({ "faulted": false })
+        )));
+        assert!(!matcher.matches(&update(
+            "mock-zid-2",
+            "sensor.contact",
+            &json!({ "faulted": true })
+        )));
+        assert!(!matcher.matches(&Event::new(Message::SessionInfo(json!({
+            "datatype": "SessionInfoType",
+            "body": []
+        })))));
+    }
+
+    #[test]
+    fn test_matching_fault_changes_in_partial_updates() {
+        let mut matcher = Filter::device(Zid::new("mock-zid-1"))
+            .kind(EventKind::Faulted)
+            .matcher();
+
+        let fault = |faulted: bool| {
+            Event::new(Message::DataUpdate(json!({
+                "datatype": "DeviceInfoDocType",
+                "body": [{
+                    "general": { "v2": { "zid": "mock-zid-1" } },
+                    "device": { "v1": { "faulted": faulted } }
+                }]
+            })))
+        };
+
+        assert!(matcher.matches(&fault(true)));
+        assert!(!matcher.matches(&fault(true)));
+        assert!(!matcher.matches(&fault(false)));
+        assert!(matcher.matches(&fault(true)));
+
+        // Each matcher tracks what it has seen separately.
+        let mut other = Filter::device(Zid::new("mock-zid-1"))
+            .kind(EventKind::Faulted)
+            .matcher();
+
+        assert!(other.matches(&fault(true)));
+        assert!(!matcher.matches(&fault(true)));
+    }
+
+    #[test]
+    fn test_matching_tamper_changes() {
+        let mut matcher = Filter::any().kind(EventKind::Tampered).matcher();
+
+        let tamper = |status: &str| {
+            Event::new(Message::DataUpdate(json!({
+                "datatype": "DeviceInfoDocType",
+                "body": [{ "general": { "v2": { "zid": "mock-zid-1", "tamperStatus": status } } }]
+            })))
+        };
+
+        assert!(matcher.matches(&tamper("tamper")));
+        assert!(!matcher.matches(&tamper("tamper")));
+        assert!(!matcher.matches(&tamper("ok")));
+        assert!(matcher.matches(&tamper("tamper")));
+    }
+
+    #[test]
+    fn test_filtering_by_device_alone() {
+        let mut matcher = Filter::device(Zid::new("mock-zid-1")).matcher();
+
+        assert!(matcher.matches(&Event::new(Message::DataUpdate(json!({
+            "datatype": "DeviceInfoDocType",
+            "body": [{ "general": { "v2": { "zid": "mock-zid-1" } } }]
+        })))));
+        assert!(matcher.matches(&Event::new(Message::DeviceInfoSet(json!({
+            "body": [{ "zid": "mock-zid-1" }]
+        })))));
+        assert!(!matcher.matches(&Event::new(Message::SessionInfo(json!({})))));
+    }
+
+    #[test]
+    fn test_filtering_mode_changes() {
+        let mut matcher = Filter::any().kind(EventKind::ModeChanged).matcher();
+
+        assert!(matcher.matches(&Event::new(Message::DataUpdate(json!({
+            "datatype": "DeviceInfoDocType",
+            "body": [{
+                "general": { "v2": { "zid": "mock-zid-panel" } },
+                "device": { "v1": { "mode": "some" } }
+            }]
+        })))));
+
+        assert!(matcher.matches(&update(
+            "mock-zid-panel",
+            "security-panel",
+            &json!({ "mode": "all" })
+        )));
+        assert!(!matcher.matches(&update(
+            "mock-zid-1",
+            "sensor.contact",
+            &json!({ "faulted": true })
+        )));
+    }
+
+    #[test]
+    fn test_filtering_by_datatype_and_context() {
+        let event = Event::new(Message::DataUpdate(json!({
+            "datatype": "HubDisconnectionEventType",
+            "context": { "affectedEntityId": "mock-zid-1" },
+            "body": [{ "statusCode": 1 }]
+        })));
+
+        assert!(
+            Filter::any()
+                .datatype("HubDisconnectionEventType")
+                .matcher()
+                .matches(&event)
+        );
+        assert!(
+            !Filter::any()
+                .datatype("DeviceInfoDocType")
+                .matcher()
+                .matches(&event)
+        );
+        assert!(
+            Filter::device(Zid::new("mock-zid-1"))
+                .matcher()
+                .matches(&event)
+        );
+        assert!(
+            !Filter::device(Zid::new("mock-zid-2"))
+                .matcher()
+                .matches(&event)
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use crate::location::{Filter, ListenerEvent, Matcher, ReconnectPolicy, Sender, Zid};
+use crate::{ApiError, Client};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+/// An event which occurred in one of the locations managed by an [`EventHub`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationEvent {
+    /// The ID of the location the event occurred in (see [`crate::location::Location::id`]).
+    pub location_id: String,
+
+    /// The event itself.
+    pub event: ListenerEvent,
+}
+
+/// Which events a subscriber to an [`EventHub`] receives.
+#[derive(Debug)]
+enum Scope {
+    All,
+    Location(String),
+    Filter(Matcher),
+}
+
+impl Scope {
+    fn matches(&mut self, event: &LocationEvent) -> bool {
+        match self {
+            Self::All => true,
+            Self::Location(location_id) => &event.location_id == location_id,
+            Self::Filter(matcher) => match &event.event {
+                ListenerEvent::Event(event) => matcher.matches(event),
+                _ => false,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    scope: Scope,
+    sender: mpsc::UnboundedSender<LocationEvent>,
+}
+
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+/// The events from a location, each tagged with its ID, ending with `None` once the location has
+/// stopped listening.
+type LocationStream =
+    futures_util::stream::BoxStream<'static, (String, Option<Result<ListenerEvent, ApiError>>)>;
+
+/// The handles used to send events to each location still being listened to.
+type Senders = Arc<Mutex<HashMap<String, Sender>>>;
+
+/// Listens for events in many locations at once, and fans them out to subscribers.
+///
+/// Every location is listened to on a single background task, using listeners which reconnect
+/// automatically (see [`crate::location::Listener::reconnecting`]). Each event is tagged with
+/// the location it occurred in, and delivered to every subscriber interested in it.
+///
+/// Events are only delivered to subscribers which exist when the event is received. To receive
+/// every event from the moment the hub connects, subscribe using the [`EventHubBuilder`].
+///
+/// A location stops being listened to once its listener gives up reconnecting (see
+/// [`ReconnectPolicy::max_attempts`]). The background task stops when the hub is dropped.
+///
+/// See [`Client::event_hub`].
+#[derive(Debug)]
+pub struct EventHub {
+    subscribers: Subscribers,
+    senders: Senders,
+    task: AbortHandle,
+}
+
+impl EventHub {
+    /// Receive every event, from every location.
+    #[must_use]
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<LocationEvent> {
+        self.add_subscriber(Scope::All)
+    }
+
+    /// Receive every event which occurs in the given location.
+    #[must_use]
+    pub fn subscribe_to_location(
+        &self,
+        location_id: &str,
+    ) -> mpsc::UnboundedReceiver<LocationEvent> {
+        self.add_subscriber(Scope::Location(location_id.to_string()))
+    }
+
+    /// Receive every event about the given (alarm) device, from any location.
+    #[must_use]
+    pub fn subscribe_to_device(&self, zid: &Zid) -> mpsc::UnboundedReceiver<LocationEvent> {
+        self.add_subscriber(Scope::Filter(Filter::device(zid.clone()).matcher()))
+    }
+
+    /// Receive the events which match a filter, from any location.
+    ///
+    /// See [`crate::location::Listener::subscribe`].
+    #[must_use]
+    pub fn subscribe_filtered(&self, filter: Filter) -> mpsc::UnboundedReceiver<LocationEvent> {
+        self.add_subscriber(Scope::Filter(filter.matcher()))
+    }
+
+    /// The IDs of the locations being listened to.
+    #[must_use]
+    pub fn location_ids(&self) -> Vec<String> {
+        lock(&self.senders).keys().cloned().collect()
+    }
+
+    /// Get a handle which can send events to the given location, or `None` if the location is
+    /// not being listened to.
+    #[must_use]
+    pub fn sender(&self, location_id: &str) -> Option<Sender> {
+        lock(&self.senders).get(location_id).cloned()
+    }
+
+    fn add_subscriber(&self, scope: Scope) -> mpsc::UnboundedReceiver<LocationEvent> {
+        // Once every location has stopped, the subscriber is dropped straight away so that the
+        // receiver closes rather than waiting forever.
+        if self.task.is_finished() {
+            return mpsc::unbounded_channel().1;
+        }
+
+        add_subscriber(&self.subscribers, scope)
+    }
+}
+
+impl Drop for EventHub {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Deliver an event to every subscriber interested in it, forgetting any subscribers which
+/// have gone away (whether or not they were interested).
+fn publish(subscribers: &Subscribers, event: &LocationEvent) {
+    lock(subscribers).retain_mut(|subscriber| {
+        !subscriber.sender.is_closed()
+            && (!subscriber.scope.matches(event) || subscriber.sender.send(event.clone()).is_ok())
+    });
+}
+
+fn add_subscriber(
+    subscribers: &Subscribers,
+    scope: Scope,
+) -> mpsc::UnboundedReceiver<LocationEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    lock(subscribers).push(Subscriber { scope, sender });
+
+    receiver
+}
+
+fn lock<T>(state: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    state
+        .lock()
+        .expect("Event hub state should not be poisoned")
+}
+
+/// A builder for an [`EventHub`].
+///
+/// See [`Client::event_hub`].
+#[derive(Debug)]
+pub struct EventHubBuilder {
+    client: Client,
+    location_ids: Option<Vec<String>>,
+    reconnect: ReconnectPolicy,
+    subscribers: Subscribers,
+}
+
+impl EventHubBuilder {
+    /// Only listen to the locations with the given IDs, rather than every location in the
+    /// account.
+    #[must_use]
+    pub fn locations(mut self, location_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.location_ids = Some(location_ids.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// How to reconnect to each location after its connection is lost.
+    ///
+    /// Defaults to [`ReconnectPolicy::default`].
+    #[must_use]
+    pub const fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Like [`EventHub::subscribe`], but receives every event from the moment the hub
+    /// connects.
+    ///
+    /// The receiver closes if the hub fails to connect.
+    #[must_use]
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<LocationEvent> {
+        add_subscriber(&self.subscribers, Scope::All)
+    }
+
+    /// Like [`EventHub::subscribe_to_location`], but receives every event from the moment the
+    /// hub connects.
+    #[must_use]
+    pub fn subscribe_to_location(
+        &self,
+        location_id: &str,
+    ) -> mpsc::UnboundedReceiver<LocationEvent> {
+        add_subscriber(&self.subscribers, Scope::Location(location_id.to_string()))
+    }
+
+    /// Like [`EventHub::subscribe_to_device`], but receives every event from the moment the hub
+    /// connects.
+    #[must_use]
+    pub fn subscribe_to_device(&self, zid: &Zid) -> mpsc::UnboundedReceiver<LocationEvent> {
+        add_subscriber(
+            &self.subscribers,
+            Scope::Filter(Filter::device(zid.clone()).matcher()),
+        )
+    }
+
+    /// Like [`EventHub::subscribe_filtered`], but receives every event from the moment the hub
+    /// connects.
+    #[must_use]
+    pub fn subscribe_filtered(&self, filter: Filter) -> mpsc::UnboundedReceiver<LocationEvent> {
+        add_subscriber(&self.subscribers, Scope::Filter(filter.matcher()))
+    }
+
+    /// Connect to every location, and start listening for events in the background.
+    ///
+    /// Locations which cannot be connected to are logged and skipped, so that one unreachable
+    /// location doesn't stop the others from being listened to (see
+    /// [`EventHub::location_ids`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the locations could not be retrieved, or if a connection could not
+    /// be established with any of them.
+    pub async fn connect(self) -> Result<EventHub, ApiError> {
+        let mut locations = self.client.get_locations().await?;
+
+        if let Some(location_ids) = &self.location_ids {
+            locations.retain(|location| location_ids.iter().any(|id| id == location.id()));
+
+            for id in location_ids {
+                if !locations.iter().any(|location| location.id() == id) {
+                    log::warn!("Location {id} was not found, so will not be listened to");
+                }
+            }
+        }
+
+        let mut senders = HashMap::new();
+        let mut streams = Vec::new();
+        let mut failure = None;
+
+        for location in locations {
+            let location_id = location.id().to_string();
+
+            let listener = match location.get_listener().await {
+                Ok(listener) => listener.reconnecting(self.reconnect.clone()),
+                Err(error) => {
+                    log::error!("Unable to connect to location {location_id}: {error:?}");
+
+                    failure = Some(error);
+                    continue;
+                }
+            };
+
+            senders.insert(location_id.clone(), listener.sender());
+
+            // Each stream finishes with `None`, so that the location can be forgotten once its
+            // listener has stopped.
+            let stopped =
+                futures_util::stream::once(std::future::ready((location_id.clone(), None)));
+
+            streams.push(
+                listener
+                    .into_stream()
+                    .map(move |event| (location_id.clone(), Some(event)))
+                    .chain(stopped)
+                    .boxed(),
+            );
+        }
+
+        if let (true, Some(error)) = (streams.is_empty(), failure) {
+            return Err(error);
+        }
+
+        let senders = Arc::new(Mutex::new(senders));
+
+        let task = tokio::spawn(Self::run(
+            futures_util::stream::select_all(streams),
+            Arc::clone(&self.subscribers),
+            Arc::clone(&senders),
+        ))
+        .abort_handle();
+
+        Ok(EventHub {
+            subscribers: self.subscribers,
+            senders,
+            task,
+        })
+    }
+
+    /// Fan out events from every location until they have all stopped.
+    async fn run(
+        mut events: futures_util::stream::SelectAll<LocationStream>,
+        subscribers: Subscribers,
+        senders: Senders,
+    ) {
+        while let Some((location_id, event)) = events.next().await {
+            match event {
+                Some(Ok(event)) => publish(&subscribers, &LocationEvent { location_id, event }),
+                Some(Err(error)) => {
+                    log::error!("Error receiving event from location {location_id}: {error:?}");
+                }
+                None => {
+                    log::warn!("Location {location_id} has stopped listening");
+
+                    lock(&senders).remove(&location_id);
+                }
+            }
+        }
+
+        log::info!("Every location has stopped listening, stopping event hub");
+
+        // Dropping the subscribers closes their receivers.
+        lock(&subscribers).clear();
+    }
+}
+
+impl Client {
+    /// Listen for events in every location in the account, using a single background task.
+    ///
+    /// Events are tagged with the location they occurred in, and can be subscribed to by
+    /// location or by device (see [`EventHub`]).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let hub = client.event_hub();
+    ///
+    /// // Subscribing before connecting means no events are missed.
+    /// let mut events = hub.subscribe();
+    ///
+    /// let _hub = hub.connect()
+    ///      .await
+    ///      .expect("Connecting to every location should not fail");
+    ///
+    /// while let Some(event) = events.recv().await {
+    ///     println!("New event in {}: {:#?}", event.location_id, event.event);
+    /// }
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn event_hub(&self) -> EventHubBuilder {
+        EventHubBuilder {
+            client: self.clone(),
+            location_ids: None,
+            reconnect: ReconnectPolicy::default(),
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::{Event, Message};
+    use serde_json::json;
+
+    #[test]
+    fn test_matching_device_events() {
+        let mut scope = Scope::Filter(Filter::device(Zid::new("mock-zid-1")).matcher());
+
+        let update = |zid: &str| LocationEvent {
+            location_id: "mock-location-id".to_string(),
+            event: ListenerEvent::Event(Event::new(Message::DataUpdate(json!({
+                "datatype": "DeviceInfoDocType",
+                "body": [{ "general": { "v2": { "zid": zid } } }]
+            })))),
+        };
+
+        assert!(scope.matches(&update("mock-zid-1")));
+        assert!(!scope.matches(&update("mock-zid-2")));
+        assert!(!scope.matches(&LocationEvent {
+            location_id: "mock-location-id".to_string(),
+            event: ListenerEvent::Disconnected,
+        }));
+    }
+}
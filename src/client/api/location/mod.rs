@@ -1,32 +1,46 @@
+mod alarm;
+mod connection;
 mod event;
+mod filter;
+mod hub;
 
 use crate::client::Client;
-use crate::helper;
 use crate::helper::url::Url;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use serde_json::Value;
 
 use crate::client::api::error::ApiError;
-use crate::client::{api::RingApi, authentication::Tokens};
+use crate::client::api::{Listing, RingApi};
+pub use alarm::*;
+pub use connection::*;
 pub use event::*;
+pub use filter::*;
+pub use hub::*;
 
 /// A location in a Ring account.
-#[derive(Debug)]
-pub struct Location<'a> {
-    session: &'a Client,
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub(crate) session: Client,
 
     /// Data about the location.
     pub data: LocationData,
 }
 
-impl Location<'_> {
-    pub(crate) const fn new(session: &Client, data: LocationData) -> Location<'_> {
-        Location { session, data }
+impl Location {
+    pub(crate) const fn new(session: Client, data: LocationData) -> Self {
+        Self { session, data }
+    }
+
+    /// The ID Ring uses to identify the location.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.data.id
     }
 }
 
 #[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LocationAddress {
     pub address1: String,
     pub address2: String,
@@ -39,14 +53,14 @@ pub struct LocationAddress {
 }
 
 #[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct GeoCoordinates {
     pub latitude: f64,
     pub longitude: f64,
 }
 
 #[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GeoServiceVerified {
     Verified,
@@ -55,7 +69,7 @@ pub enum GeoServiceVerified {
 }
 
 /// Data about a location in a Ring account.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LocationData {
     #[serde(rename = "location_id")]
     pub(crate) id: String,
@@ -93,23 +107,19 @@ pub struct LocationData {
 
 #[derive(Debug, Deserialize)]
 struct Response {
-    user_locations: Vec<LocationData>,
+    user_locations: Vec<Value>,
 }
 
 impl RingApi {
-    pub(crate) async fn get_location_data(
-        &self,
-        tokens: &Tokens,
-    ) -> Result<Vec<LocationData>, ApiError> {
-        Ok(self
-            .client
-            .get(helper::url::get_base_url(&Url::Locations))
-            .header("User-Agent", self.operating_system.get_user_agent())
-            .bearer_auth(&tokens.access_token)
-            .send()
-            .await?
-            .json::<Response>()
+    /// Get every location in the account, decoding each location separately so that one
+    /// location which can't be decoded doesn't prevent the others from being returned.
+    pub(crate) async fn get_location_data(&self) -> Result<Listing<LocationData>, ApiError> {
+        Ok(Listing::decode(
+            self.request::<Response>(|client| {
+                client.get(self.endpoints.get_base_url(&Url::Locations))
+            })
             .await?
-            .user_locations)
+            .user_locations,
+        ))
     }
 }
@@ -1,5 +1,9 @@
+/// Support for the capabilities of Ring devices (such as cameras and lights)
+pub mod capability;
 /// Support for Ring devices (such as doorbells and cameras)
 pub mod device;
+/// Support for the event history of Ring devices (such as dings and motion)
+pub mod history;
 /// Support for Ring locations (such as homes and other properties)
 pub mod location;
 /// Support for Ring users (such as profile management)
@@ -8,21 +12,222 @@ pub mod session;
 pub mod ticket;
 
 mod error;
+mod listing;
+mod recording;
+mod retry;
+mod snapshot;
 
+use crate::client::authentication::{TokenManager, Tokens};
 use crate::helper::OperatingSystem;
+use crate::helper::url::Endpoints;
+use chrono::{DateTime, Utc};
 pub use error::ApiError;
+pub use listing::{ItemError, Listing};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+pub use retry::RetryPolicy;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct RingApi {
     client: reqwest::Client,
     operating_system: OperatingSystem,
+    endpoints: Endpoints,
+    tokens: Arc<TokenManager>,
+    retry_policy: RetryPolicy,
 }
 
 impl RingApi {
-    pub fn new(operating_system: OperatingSystem) -> Self {
+    pub fn new(
+        operating_system: OperatingSystem,
+        endpoints: Endpoints,
+        tokens: Arc<TokenManager>,
+        retry_policy: RetryPolicy,
+    ) -> Self {
         Self {
             client: reqwest::Client::new(),
             operating_system,
+            endpoints,
+            tokens,
+            retry_policy,
         }
     }
+
+    /// Send an authenticated request to Ring, and decode the JSON response.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder + Sync,
+    ) -> Result<T, ApiError> {
+        let body = self.send_and_read(request, Response::text).await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Send an authenticated request to Ring, retrying transient failures according to the
+    /// [`RetryPolicy`].
+    ///
+    /// Responses with an unsuccessful status are turned into the matching [`ApiError`].
+    async fn send(
+        &self,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder + Sync,
+    ) -> Result<Response, ApiError> {
+        self.send_and_read(request, |response| std::future::ready(Ok(response)))
+            .await
+    }
+
+    /// Send an authenticated request to Ring, and read its response with `read`, retrying
+    /// transient failures (including those while the response is being read) according to the
+    /// [`RetryPolicy`].
+    ///
+    /// Responses with an unsuccessful status are turned into the matching [`ApiError`].
+    async fn send_and_read<T, F>(
+        &self,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder + Sync,
+        read: impl Fn(Response) -> F + Sync,
+    ) -> Result<T, ApiError>
+    where
+        F: Future<Output = reqwest::Result<T>>,
+    {
+        let idempotent = request(&self.client)
+            .build()
+            .is_ok_and(|request| request.method().is_idempotent());
+
+        let mut attempt = 1;
+
+        loop {
+            let result = match self.send_once(&request).await {
+                Ok(response) => read(response).await.map_err(ApiError::from),
+                Err(error) => Err(error),
+            };
+
+            let error = match result {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            let Some(delay) = self.retry_policy.retry_delay(attempt, idempotent, &error) else {
+                return Err(error);
+            };
+
+            log::warn!(
+                "Request to Ring failed (attempt {attempt}), retrying in {delay:?}: {error}"
+            );
+
+            tokio::time::sleep(delay).await;
+
+            attempt += 1;
+        }
+    }
+
+    /// Send an authenticated request to Ring once.
+    ///
+    /// The tokens are refreshed before the request is sent if they're about to expire. If Ring
+    /// still rejects the access token (for example, because it was revoked early), the tokens
+    /// are refreshed and the request is retried once.
+    async fn send_once(
+        &self,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder + Sync,
+    ) -> Result<Response, ApiError> {
+        let tokens = self
+            .tokens
+            .refresh_if_needed()
+            .await
+            .map_err(ApiError::AuthenticationRefreshFailed)?;
+
+        let response = self.authenticate(&request, &tokens).send().await?;
+
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return check_status(response).await;
+        }
+
+        log::warn!("Ring rejected the access token, refreshing the tokens and retrying");
+
+        let tokens = self
+            .tokens
+            .refresh(&tokens)
+            .await
+            .map_err(ApiError::AuthenticationRefreshFailed)?;
+
+        let response = self.authenticate(&request, &tokens).send().await?;
+
+        check_status(response).await
+    }
+
+    fn authenticate(
+        &self,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder + Sync,
+        tokens: &Tokens,
+    ) -> RequestBuilder {
+        request(&self.client)
+            .header("User-Agent", self.operating_system.get_user_agent())
+            .bearer_auth(&tokens.access_token)
+    }
+}
+
+/// Turn a response with an unsuccessful status into the matching [`ApiError`], keeping the raw
+/// body for diagnostics.
+async fn check_status(response: Response) -> Result<Response, ApiError> {
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+
+    let body = response.text().await.unwrap_or_default();
+
+    log::warn!("Ring responded with an unsuccessful status ({status}): {body}");
+
+    Err(match status {
+        StatusCode::UNAUTHORIZED => ApiError::Unauthorized,
+        StatusCode::NOT_FOUND => ApiError::NotFound { body },
+        StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { retry_after, body },
+        status if status.is_server_error() => ApiError::ServerError { status, body },
+        status => ApiError::UnexpectedStatus { status, body },
+    })
+}
+
+/// Parse a `Retry-After` header, which is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?;
+
+    Some(
+        (retry_at.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_date_in_the_past() {
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_retry_after() {
+        assert_eq!(parse_retry_after("soon"), None);
+    }
 }
@@ -0,0 +1,224 @@
+use crate::client::api::RingApi;
+use crate::client::api::check_status;
+use crate::client::api::error::ApiError;
+use crate::device::Device;
+use crate::helper::url::Url;
+use reqwest::Response;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use serde::Deserialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// How many times a download is attempted (including resuming it after the connection drops)
+/// before giving up.
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+
+#[derive(Deserialize)]
+struct RecordingUrl {
+    url: String,
+}
+
+impl Device {
+    /// Get a temporary URL which the recording of an item in the history of the device can be
+    /// downloaded from.
+    ///
+    /// The ID of the recording is the ID of the history item (see
+    /// [`crate::history::HistoryItem::id`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::UnsupportedDevice`] if the device doesn't have a camera (see
+    /// [`Device::as_camera`]), or an error if the recording does not exist (or is still being
+    /// processed), or if Ring could not be reached.
+    pub async fn recording_url(&self, ding_id: u64) -> Result<String, ApiError> {
+        self.as_camera()
+            .ok_or(ApiError::UnsupportedDevice("recordings"))?;
+
+        self.session.inner.api.get_recording_url(ding_id).await
+    }
+
+    /// Download the recording of an item in the history of the device.
+    ///
+    /// The recording is written to `writer` as it is downloaded. If the connection drops part
+    /// way through, the download is resumed from where it stopped (after waiting as long as the
+    /// client's [`crate::RetryPolicy`] would between retries), rather than starting over.
+    ///
+    /// Returns the number of bytes written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::history::HistoryQuery;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let devices = client.get_devices()
+    ///      .await
+    ///      .expect("Getting devices should not fail");
+    ///
+    /// let device = devices
+    ///      .first()
+    ///      .expect("There should be at least one device");
+    ///
+    /// let mut recording = Vec::new();
+    ///
+    /// device.download_recording(7_000_000_000_000_000_001, &mut recording)
+    ///      .await
+    ///      .expect("Downloading the recording should not fail");
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::UnsupportedDevice`] if the device doesn't have a camera,
+    /// [`ApiError::ResumedFromWrongPosition`] if an interrupted download couldn't be resumed
+    /// from where it stopped, or an error if the recording could not be found, if the download
+    /// could not be completed, or if writing the recording failed.
+    pub async fn download_recording(
+        &self,
+        ding_id: u64,
+        mut writer: impl AsyncWrite + Unpin + Send,
+    ) -> Result<u64, ApiError> {
+        let url = self.recording_url(ding_id).await?;
+
+        self.session.inner.api.download(&url, &mut writer).await
+    }
+}
+
+impl RingApi {
+    pub(crate) async fn get_recording_url(&self, ding_id: u64) -> Result<String, ApiError> {
+        let recording = self
+            .request::<RecordingUrl>(|client| {
+                client
+                    .get(self.endpoints.get_base_url(&Url::Recording { ding_id }))
+                    .query(&[("disable_redirect", true)])
+            })
+            .await?;
+
+        Ok(recording.url)
+    }
+
+    /// Download a file to `writer`, resuming the download if the connection drops.
+    ///
+    /// The URL is expected to be presigned (i.e. a recording URL), so the request is not
+    /// authenticated.
+    async fn download(
+        &self,
+        url: &str,
+        writer: &mut (impl AsyncWrite + Unpin + Send),
+    ) -> Result<u64, ApiError> {
+        let mut written = 0;
+        let mut attempt = 1;
+
+        loop {
+            match self.download_from(url, &mut written, writer).await {
+                Ok(()) => break,
+                Err(ApiError::RequestError(error)) if attempt < DOWNLOAD_ATTEMPTS => {
+                    let delay = self.retry_policy.backoff(attempt);
+
+                    log::warn!(
+                        "Download interrupted after {written} bytes (attempt {attempt}), resuming in {delay:?}: {error}"
+                    );
+
+                    tokio::time::sleep(delay).await;
+
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        writer.flush().await.map_err(ApiError::WriteFailed)?;
+
+        Ok(written)
+    }
+
+    /// Download a file to `writer`, starting from the given number of bytes, and counting the
+    /// bytes written as they are.
+    async fn download_from(
+        &self,
+        url: &str,
+        written: &mut u64,
+        writer: &mut (impl AsyncWrite + Unpin + Send),
+    ) -> Result<(), ApiError> {
+        let mut request = self.client.get(url);
+
+        if *written > 0 {
+            request = request.header(RANGE, format!("bytes={written}-"));
+        }
+
+        let mut response = check_status(request.send().await?).await?;
+
+        // If the server ignored the range, the file is sent from the start, so skip the bytes
+        // which have already been written. Otherwise, the bytes sent must carry on from them.
+        let mut skip = if response.status() == StatusCode::PARTIAL_CONTENT {
+            let start = range_start(&response);
+
+            if start != Some(*written) {
+                return Err(ApiError::ResumedFromWrongPosition {
+                    written: *written,
+                    start,
+                });
+            }
+
+            0
+        } else {
+            *written
+        };
+
+        while let Some(mut chunk) = response.chunk().await? {
+            if skip > 0 {
+                let skipped = chunk.len().min(usize::try_from(skip).unwrap_or(usize::MAX));
+
+                chunk = chunk.slice(skipped..);
+                skip -= skipped as u64;
+            }
+
+            writer
+                .write_all(&chunk)
+                .await
+                .map_err(ApiError::WriteFailed)?;
+
+            *written += chunk.len() as u64;
+        }
+
+        Ok(())
+    }
+}
+
+/// The first byte sent in a partial response, from its `Content-Range` (i.e. `bytes 100-199/200`).
+fn range_start(response: &Response) -> Option<u64> {
+    parse_range_start(response.headers().get(CONTENT_RANGE)?.to_str().ok()?)
+}
+
+fn parse_range_start(content_range: &str) -> Option<u64> {
+    content_range
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_the_start_of_a_range() {
+        assert_eq!(parse_range_start("bytes 100-199/200"), Some(100));
+        assert_eq!(parse_range_start("bytes 0-199/*"), Some(0));
+        assert_eq!(parse_range_start("bytes */200"), None);
+        assert_eq!(parse_range_start("items 100-199/200"), None);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::client::api::ApiError;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Controls how requests to the Ring REST API are retried when they fail with a transient
+/// error (such as a `503 Service Unavailable`, or the connection being reset).
+///
+/// Only idempotent requests (i.e. fetching devices and locations) are retried, unless
+/// [`RetryPolicy::retry_non_idempotent`] is enabled.
+///
+/// By default, requests are not retried. Use [`RetryPolicy::max_attempts`] to enable retries.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use ring_client::{Client, RetryPolicy};
+/// use ring_client::OperatingSystem;
+///
+/// let client = Client::builder("Home Automation", "mock-system-id", OperatingSystem::Ios)
+///     .retry_policy(
+///         RetryPolicy::default()
+///             .max_attempts(5)
+///             .base_delay(Duration::from_millis(500))
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    retryable_statuses: Vec<StatusCode>,
+    retry_request_errors: bool,
+    respect_retry_after: bool,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_request_errors: true,
+            respect_retry_after: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The maximum number of times a request is attempted, including the first attempt.
+    ///
+    /// Defaults to `1` (i.e. requests are not retried).
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// The delay before the first retry, which is doubled for each subsequent retry.
+    ///
+    /// Defaults to 200 milliseconds.
+    #[must_use]
+    pub const fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// The longest delay between retries.
+    ///
+    /// Defaults to ten seconds.
+    #[must_use]
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Randomise each delay (between half and all of it), so that many clients failing at
+    /// once don't all retry at the same moment.
+    ///
+    /// Enabled by default.
+    #[must_use]
+    pub const fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// The response statuses which should be retried.
+    ///
+    /// Defaults to `429`, `500`, `502`, `503` and `504`.
+    #[must_use]
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether requests which failed to connect, or timed out, should be retried.
+    ///
+    /// Idempotent requests are also retried if the connection is reset, or the response is
+    /// cut short while it's being read.
+    ///
+    /// Enabled by default.
+    #[must_use]
+    pub const fn retry_request_errors(mut self, enabled: bool) -> Self {
+        self.retry_request_errors = enabled;
+        self
+    }
+
+    /// Whether to wait for as long as Ring asks (using the `Retry-After` header) when
+    /// requests are rate limited, instead of the usual delay.
+    ///
+    /// If Ring asks to wait for longer than [`RetryPolicy::max_delay`], the request is not
+    /// retried, and [`ApiError::RateLimited`] is returned instead.
+    ///
+    /// Enabled by default.
+    #[must_use]
+    pub const fn respect_retry_after(mut self, enabled: bool) -> Self {
+        self.respect_retry_after = enabled;
+        self
+    }
+
+    /// Whether requests which are not idempotent (such as creating a session) should also be
+    /// retried.
+    ///
+    /// Disabled by default, as retrying these requests may repeat their side effects.
+    #[must_use]
+    pub const fn retry_non_idempotent(mut self, enabled: bool) -> Self {
+        self.retry_non_idempotent = enabled;
+        self
+    }
+
+    /// How long to wait before making the given attempt again, or `None` if the request should
+    /// not be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        idempotent: bool,
+        error: &ApiError,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts
+            || (!idempotent && !self.retry_non_idempotent)
+            || !self.is_retryable(error, idempotent)
+        {
+            return None;
+        }
+
+        if let ApiError::RateLimited {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            if self.respect_retry_after {
+                // Rather than stalling for as long as the server asks, give up (returning the
+                // rate limit error) once it asks for longer than the maximum delay.
+                return (*retry_after <= self.max_delay).then_some(*retry_after);
+            }
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    fn is_retryable(&self, error: &ApiError, idempotent: bool) -> bool {
+        match error {
+            // Once a request has been sent, it may have reached Ring, so the connection being
+            // reset (or the response being cut short) is only retried if that's harmless. JSON
+            // is decoded separately, so decoding errors come from reading the response's text.
+            ApiError::RequestError(error) => {
+                self.retry_request_errors
+                    && (error.is_connect()
+                        || error.is_timeout()
+                        || (idempotent
+                            && (error.is_request() || error.is_body() || error.is_decode())))
+            }
+            ApiError::RateLimited { .. } => self
+                .retryable_statuses
+                .contains(&StatusCode::TOO_MANY_REQUESTS),
+            ApiError::NotFound { .. } => self.retryable_statuses.contains(&StatusCode::NOT_FOUND),
+            ApiError::ServerError { status, .. } | ApiError::UnexpectedStatus { status, .. } => {
+                self.retryable_statuses.contains(status)
+            }
+            _ => false,
+        }
+    }
+
+    /// The exponential backoff after the given attempt, capped at the maximum delay.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        if !self.jitter {
+            return delay;
+        }
+
+        let half = delay / 2;
+
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+/// A cheap, non-cryptographic random number between 0 and 1.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+
+    #[allow(clippy::cast_precision_loss)]
+    let fraction = (hasher.finish() >> 11) as f64 / (1_u64 << 53) as f64;
+
+    fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error() -> ApiError {
+        ApiError::ServerError {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_not_retrying_by_default() {
+        assert_eq!(
+            RetryPolicy::default().retry_delay(1, true, &server_error()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = RetryPolicy::default()
+            .max_attempts(5)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(300))
+            .jitter(false);
+
+        assert_eq!(
+            policy.retry_delay(1, true, &server_error()),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay(2, true, &server_error()),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.retry_delay(3, true, &server_error()),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(policy.retry_delay(5, true, &server_error()), None);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_delay() {
+        let policy = RetryPolicy::default()
+            .max_attempts(2)
+            .base_delay(Duration::from_millis(100));
+
+        let delay = policy
+            .retry_delay(1, true, &server_error())
+            .expect("Request should be retried");
+
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_respecting_retry_after() {
+        let policy = RetryPolicy::default()
+            .max_attempts(2)
+            .max_delay(Duration::from_secs(60));
+
+        let rate_limited = ApiError::RateLimited {
+            retry_after: Some(Duration::from_secs(30)),
+            body: String::new(),
+        };
+
+        assert_eq!(
+            policy.retry_delay(1, true, &rate_limited),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_not_waiting_longer_than_the_max_delay() {
+        let policy = RetryPolicy::default()
+            .max_attempts(2)
+            .max_delay(Duration::from_secs(10));
+
+        let rate_limited = ApiError::RateLimited {
+            retry_after: Some(Duration::from_secs(3600)),
+            body: String::new(),
+        };
+
+        assert_eq!(policy.retry_delay(1, true, &rate_limited), None);
+    }
+
+    #[test]
+    fn test_not_retrying_non_idempotent_requests() {
+        let policy = RetryPolicy::default().max_attempts(2);
+
+        assert_eq!(policy.retry_delay(1, false, &server_error()), None);
+        assert!(
+            policy
+                .retry_non_idempotent(true)
+                .retry_delay(1, false, &server_error())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_not_retrying_unretryable_errors() {
+        let policy = RetryPolicy::default().max_attempts(2);
+
+        assert_eq!(policy.retry_delay(1, true, &ApiError::Unauthorized), None);
+        assert_eq!(
+            policy.retry_delay(
+                1,
+                true,
+                &ApiError::NotFound {
+                    body: String::new()
+                }
+            ),
+            None
+        );
+    }
+}
@@ -1,6 +1,5 @@
 use crate::client::api::RingApi;
 use crate::client::api::error::ApiError;
-use crate::client::authentication::Tokens;
 use crate::helper::url::Url;
 use crate::{constant, helper};
 use serde::Deserialize;
@@ -39,26 +38,21 @@ impl RingApi {
         &self,
         display_name: &str,
         system_id: &str,
-        tokens: &Tokens,
     ) -> Result<Session, ApiError> {
-        Ok(self
-            .client
-            .post(helper::url::get_base_url(&Url::Session))
-            .header("User-Agent", self.operating_system.get_user_agent())
-            .bearer_auth(&tokens.access_token)
-            .json(&json!({
-                "device": {
-                    "hardware_id": helper::hardware::generate_hardware_id(system_id),
-                    "os": self.operating_system.to_string(),
-                    "metadata": {
-                        "api_version": constant::API_VERSION,
-                        "device_model": display_name
+        self.request::<Session>(|client| {
+            client
+                .post(self.endpoints.get_base_url(&Url::Session))
+                .json(&json!({
+                    "device": {
+                        "hardware_id": helper::hardware::generate_hardware_id(system_id),
+                        "os": self.operating_system.to_string(),
+                        "metadata": {
+                            "api_version": constant::API_VERSION,
+                            "device_model": display_name
+                        }
                     }
-                }
-            }))
-            .send()
-            .await?
-            .json::<Session>()
-            .await?)
+                }))
+        })
+        .await
     }
 }
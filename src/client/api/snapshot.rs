@@ -0,0 +1,149 @@
+use crate::client::api::RingApi;
+use crate::client::api::error::ApiError;
+use crate::device::Device;
+use crate::helper::url::Url;
+use bytes::Bytes;
+use reqwest::Response;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// How long to wait between checking whether a new snapshot has been captured.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How many times to check whether a new snapshot has been captured, before giving up.
+const POLL_ATTEMPTS: u32 = 40;
+
+#[derive(Deserialize)]
+struct Timestamps {
+    timestamps: Vec<Timestamp>,
+}
+
+#[derive(Deserialize)]
+struct Timestamp {
+    doorbot_id: u64,
+
+    /// When the snapshot was captured, in milliseconds since the Unix epoch.
+    timestamp: u64,
+}
+
+impl Device {
+    /// Get the latest snapshot (a JPEG image) captured by the camera.
+    ///
+    /// Ring captures snapshots periodically, so the snapshot may be a few minutes old. Use
+    /// [`Device::request_fresh_snapshot`] to capture a new one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.login(Credentials::RefreshToken("".to_string()))
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    ///
+    /// let devices = client.get_devices()
+    ///      .await
+    ///      .expect("Getting devices should not fail");
+    ///
+    /// let camera = devices
+    ///      .first()
+    ///      .expect("There should be at least one device");
+    ///
+    /// let snapshot = camera.snapshot()
+    ///      .await
+    ///      .expect("Getting the snapshot should not fail");
+    ///
+    /// println!("The snapshot is {} bytes", snapshot.len());
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::UnsupportedDevice`] if the device is not a camera or doorbell, or
+    /// an error if the snapshot could not be fetched.
+    pub async fn snapshot(&self) -> Result<Bytes, ApiError> {
+        let device_id = self.snapshot_device_id()?;
+
+        self.session.inner.api.get_snapshot(device_id).await
+    }
+
+    /// Capture a new snapshot from the camera, and wait for it to be available.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::UnsupportedDevice`] if the device is not a camera or doorbell,
+    /// [`ApiError::SnapshotNotUpdated`] if Ring does not capture the snapshot within twenty
+    /// seconds, or an error if Ring could not be reached.
+    pub async fn request_fresh_snapshot(&self) -> Result<Bytes, ApiError> {
+        let device_id = self.snapshot_device_id()?;
+        let api = &self.session.inner.api;
+
+        let previous = api.get_snapshot_timestamp(device_id).await?;
+
+        api.request_snapshot_update(device_id).await?;
+
+        for _ in 0..POLL_ATTEMPTS {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            if api.get_snapshot_timestamp(device_id).await? > previous {
+                return api.get_snapshot(device_id).await;
+            }
+        }
+
+        Err(ApiError::SnapshotNotUpdated)
+    }
+
+    fn snapshot_device_id(&self) -> Result<u64, ApiError> {
+        self.id()
+            .filter(|_| self.as_camera().is_some())
+            .ok_or(ApiError::UnsupportedDevice("snapshots"))
+    }
+}
+
+impl RingApi {
+    pub(crate) async fn get_snapshot(&self, device_id: u64) -> Result<Bytes, ApiError> {
+        self.send_and_read(
+            |client| client.get(self.endpoints.get_base_url(&Url::Snapshot { device_id })),
+            Response::bytes,
+        )
+        .await
+    }
+
+    /// When the latest snapshot from a device was captured, if it has one.
+    pub(crate) async fn get_snapshot_timestamp(
+        &self,
+        device_id: u64,
+    ) -> Result<Option<u64>, ApiError> {
+        let response = self
+            .request::<Timestamps>(|client| {
+                client
+                    .post(self.endpoints.get_base_url(&Url::SnapshotTimestamps))
+                    .json(&json!({ "doorbot_ids": [device_id] }))
+            })
+            .await?;
+
+        Ok(response
+            .timestamps
+            .into_iter()
+            .find(|timestamp| timestamp.doorbot_id == device_id)
+            .map(|timestamp| timestamp.timestamp))
+    }
+
+    pub(crate) async fn request_snapshot_update(&self, device_id: u64) -> Result<(), ApiError> {
+        self.send(|client| {
+            client
+                .put(self.endpoints.get_base_url(&Url::SnapshotUpdate))
+                .json(&json!({ "doorbot_ids": [device_id], "refresh": true }))
+        })
+        .await?;
+
+        Ok(())
+    }
+}
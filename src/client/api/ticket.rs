@@ -1,11 +1,10 @@
+use crate::Client;
 use crate::client::api::RingApi;
 use crate::client::api::error::ApiError;
-use crate::client::authentication::Tokens;
 use crate::helper::url::Url;
-use crate::{Client, helper};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 #[allow(missing_docs)]
 pub enum AssetStatus {
@@ -17,7 +16,7 @@ pub enum AssetStatus {
 ///
 /// This is effectively a Ring host which can be connected to for real-time streaming of Location events
 /// ([`crate::location::Location`]).
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Asset {
     uuid: String,
@@ -28,6 +27,32 @@ pub struct Asset {
     on_battery: bool,
 }
 
+impl Asset {
+    /// The ID of the asset, which is used to address messages to it.
+    #[must_use]
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    /// The kind of asset (i.e. `base_station_v1`).
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Whether the asset is online.
+    #[must_use]
+    pub const fn status(&self) -> AssetStatus {
+        self.status
+    }
+
+    /// Whether the asset is running on battery power.
+    #[must_use]
+    pub const fn on_battery(&self) -> bool {
+        self.on_battery
+    }
+}
+
 /// A ticket for a Ring host.
 ///
 /// This is effectively a session which allows for connections to Ring WebSocket servers.
@@ -42,34 +67,22 @@ pub struct Ticket {
     pub host: String,
 
     subscription_topics: Vec<String>,
-    assets: Vec<Asset>,
+    pub(crate) assets: Vec<Asset>,
 }
 
 impl RingApi {
-    pub async fn get_ticket(&self, location_id: &str, tokens: &Tokens) -> Result<Ticket, ApiError> {
-        let response = self
-            .client
-            .get(helper::url::get_base_url(&Url::Ticket))
-            .query(&[("locationID", location_id)])
-            .header("User-Agent", self.operating_system.get_user_agent())
-            .bearer_auth(&tokens.access_token)
-            .send()
-            .await?;
-
-        Ok(response.json::<Ticket>().await?)
+    pub async fn get_ticket(&self, location_id: &str) -> Result<Ticket, ApiError> {
+        self.request::<Ticket>(|client| {
+            client
+                .get(self.endpoints.get_base_url(&Url::Ticket))
+                .query(&[("locationID", location_id)])
+        })
+        .await
     }
 }
 
 impl Client {
     pub(crate) async fn get_ticket(&self, location_id: &str) -> Result<Ticket, ApiError> {
-        self.api
-            .get_ticket(
-                location_id,
-                &*self
-                    .refresh_tokens_if_needed()
-                    .await
-                    .map_err(ApiError::AuthenticationRefreshFailed)?,
-            )
-            .await
+        self.inner.api.get_ticket(location_id).await
     }
 }
@@ -36,4 +36,13 @@ pub enum AuthenticationError {
     /// Setting the session details with Ring failed.
     #[error("An error occurred while trying to set the session details with Ring")]
     SessionFailed,
+
+    /// Loading tokens from the configured token store failed.
+    #[error("An error occurred while trying to load tokens from the token store: {0}")]
+    TokenStoreFailed(#[from] super::TokenStoreError),
+
+    /// Logging in with [`crate::authentication::Credentials::Stored`] was attempted, but
+    /// either no token store is configured, or it did not contain any tokens.
+    #[error("No stored tokens were available to log in with")]
+    NoStoredTokens,
 }
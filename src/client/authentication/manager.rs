@@ -0,0 +1,256 @@
+use crate::client::authentication::{
+    AuthenticationError, RefreshTokenNotifier, RingAuth, TokenStore, TokenStoreError, Tokens,
+};
+use chrono::{TimeDelta, Utc};
+use std::sync::{Arc, Mutex as SyncMutex, Weak};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::AbortHandle;
+
+/// How long to wait before trying again when a background refresh fails.
+const BACKGROUND_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// The longest refresh skew which is honoured. Ring issues tokens which last far less than
+/// this, so a longer skew would only refresh the tokens on every request anyway.
+const MAX_REFRESH_SKEW: TimeDelta = TimeDelta::days(1);
+
+/// Owns the tokens issued by Ring, and keeps them up to date.
+#[derive(Debug)]
+pub struct TokenManager {
+    pub auth: RingAuth,
+    tokens: RwLock<Option<Arc<Tokens>>>,
+    store: Option<Arc<dyn TokenStore>>,
+    notifier: RefreshTokenNotifier,
+
+    /// How long before the access token expires that it should be refreshed.
+    refresh_skew: TimeDelta,
+
+    /// Held for the duration of a refresh, so that concurrent callers share a single refresh
+    /// rather than each exchanging (and invalidating) the refresh token.
+    refresh_lock: Mutex<()>,
+
+    /// Whether the tokens should be refreshed ahead of expiry by a background task.
+    background_refresh: bool,
+    background_task: SyncMutex<Option<AbortHandle>>,
+}
+
+impl TokenManager {
+    pub fn new(
+        auth: RingAuth,
+        store: Option<Arc<dyn TokenStore>>,
+        refresh_skew: Duration,
+        background_refresh: bool,
+    ) -> Self {
+        Self {
+            auth,
+            tokens: RwLock::new(None),
+            store,
+            notifier: RefreshTokenNotifier::new(),
+            refresh_skew: TimeDelta::from_std(refresh_skew)
+                .unwrap_or(MAX_REFRESH_SKEW)
+                .min(MAX_REFRESH_SKEW),
+            refresh_lock: Mutex::new(()),
+            background_refresh,
+            background_task: SyncMutex::new(None),
+        }
+    }
+
+    pub const fn notifier(&self) -> &RefreshTokenNotifier {
+        &self.notifier
+    }
+
+    /// The current tokens, regardless of whether they have expired.
+    pub async fn current(&self) -> Option<Arc<Tokens>> {
+        self.tokens.read().await.as_ref().map(Arc::clone)
+    }
+
+    /// Whether the tokens have expired, or will expire within the refresh skew.
+    fn needs_refresh(&self, tokens: &Tokens) -> bool {
+        tokens.expires_at - self.refresh_skew < Utc::now()
+    }
+
+    /// Get the current tokens, refreshing them first if they have expired (or are about to).
+    pub async fn refresh_if_needed(&self) -> Result<Arc<Tokens>, AuthenticationError> {
+        let current_tokens = self
+            .current()
+            .await
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        if !self.needs_refresh(&current_tokens) {
+            return Ok(current_tokens);
+        }
+
+        let _refreshing = self.refresh_lock.lock().await;
+
+        // Another caller may have already refreshed the tokens while we were waiting.
+        let current_tokens = self
+            .current()
+            .await
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        if !self.needs_refresh(&current_tokens) {
+            return Ok(current_tokens);
+        }
+
+        let replacement_tokens = self
+            .replace(self.auth.refresh_tokens(current_tokens).await?)
+            .await;
+
+        log::info!(
+            "Tokens have been replaced successfully. New expiration time: {}",
+            replacement_tokens.expires_at,
+        );
+
+        Ok(replacement_tokens)
+    }
+
+    /// Refresh the tokens, regardless of when they expire.
+    ///
+    /// This is used when Ring rejects tokens which have not yet expired. If the rejected tokens
+    /// have already been replaced by another caller in the meantime, the replacement tokens are
+    /// returned instead of refreshing again.
+    pub async fn refresh(
+        &self,
+        rejected: &Arc<Tokens>,
+    ) -> Result<Arc<Tokens>, AuthenticationError> {
+        let _refreshing = self.refresh_lock.lock().await;
+
+        let current_tokens = self
+            .current()
+            .await
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        if !Arc::ptr_eq(&current_tokens, rejected) {
+            return Ok(current_tokens);
+        }
+
+        let replacement_tokens = self
+            .replace(self.auth.refresh_tokens(current_tokens).await?)
+            .await;
+
+        log::info!(
+            "Rejected tokens have been replaced successfully. New expiration time: {}",
+            replacement_tokens.expires_at,
+        );
+
+        Ok(replacement_tokens)
+    }
+
+    /// Load the tokens saved in the token store, if one is configured and it has any.
+    pub async fn load_stored(&self) -> Result<Option<Tokens>, TokenStoreError> {
+        let Some(store) = self.store.as_ref().map(Arc::clone) else {
+            return Ok(None);
+        };
+
+        run_blocking(move || store.load()).await
+    }
+
+    /// Replace the tokens, save them to the token store (if one is configured), and notify
+    /// anyone watching for new refresh tokens.
+    ///
+    /// Failing to save the tokens is not fatal, as they are still usable for the lifetime of the
+    /// client.
+    pub async fn replace(&self, tokens: Tokens) -> Arc<Tokens> {
+        let tokens = Arc::new(tokens);
+
+        if let Some(store) = self.store.as_ref().map(Arc::clone) {
+            let saved = Arc::clone(&tokens);
+
+            if let Err(error) = run_blocking(move || store.save(&saved)).await {
+                log::error!("Failed to save tokens to the token store: {error:?}");
+            }
+        }
+
+        self.tokens.write().await.replace(Arc::clone(&tokens));
+
+        self.notifier.notify(&tokens.refresh_token);
+
+        tokens
+    }
+
+    /// Start refreshing the tokens ahead of expiry in the background, if enabled and not
+    /// already running.
+    ///
+    /// The task stops once the manager is dropped.
+    pub fn start_background_refresh(self: &Arc<Self>) {
+        if !self.background_refresh {
+            return;
+        }
+
+        let mut background_task = self
+            .background_task
+            .lock()
+            .expect("Background refresh task should not be poisoned");
+
+        if background_task.is_none() {
+            let task = tokio::spawn(Self::refresh_in_background(Arc::downgrade(self)));
+
+            background_task.replace(task.abort_handle());
+        }
+    }
+
+    async fn refresh_in_background(manager: Weak<Self>) {
+        let mut refresh_tokens = match manager.upgrade() {
+            Some(manager) => manager.notifier.subscribe(),
+            None => return,
+        };
+
+        loop {
+            let delay = {
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+
+                manager
+                    .current()
+                    .await
+                    .map_or(BACKGROUND_REFRESH_RETRY_DELAY, |tokens| {
+                        (tokens.expires_at - manager.refresh_skew - Utc::now())
+                            .to_std()
+                            .unwrap_or_default()
+                    })
+            };
+
+            tokio::select! {
+                () = tokio::time::sleep(delay) => {}
+                changed = refresh_tokens.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+
+                    // The tokens were replaced elsewhere, so work out when they now expire.
+                    continue;
+                }
+            }
+
+            let refreshed = match manager.upgrade() {
+                Some(manager) => manager.refresh_if_needed().await,
+                None => return,
+            };
+
+            if let Err(error) = refreshed {
+                log::error!("Failed to refresh tokens in the background: {error:?}");
+
+                tokio::time::sleep(BACKGROUND_REFRESH_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Call the token store on the blocking thread pool, as stores are synchronous and typically
+/// access the file system (or the network).
+async fn run_blocking<T: Send + 'static>(
+    operation: impl FnOnce() -> Result<T, TokenStoreError> + Send + 'static,
+) -> Result<T, TokenStoreError> {
+    tokio::task::spawn_blocking(operation)
+        .await
+        .unwrap_or_else(|error| Err(TokenStoreError::Other(Box::new(error))))
+}
+
+impl Drop for TokenManager {
+    fn drop(&mut self) {
+        if let Ok(Some(task)) = self.background_task.get_mut().map(Option::take) {
+            task.abort();
+        }
+    }
+}
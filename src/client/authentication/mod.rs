@@ -1,12 +1,19 @@
 mod error;
+mod manager;
+mod notifier;
+mod store;
 
 pub use error::AuthenticationError;
+pub use store::{FileTokenStore, TokenStore, TokenStoreError};
 
-use crate::helper::url::Url;
-use crate::{Client, helper};
+pub(crate) use manager::TokenManager;
+pub(crate) use notifier::RefreshTokenNotifier;
+
+use crate::Client;
+use crate::helper::url::{Endpoints, Url};
 use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fmt::Debug;
 use std::ops::Add;
@@ -14,14 +21,19 @@ use std::sync::Arc;
 
 use crate::helper::OperatingSystem;
 
-#[derive(Debug, Serialize)]
-pub(crate) struct Tokens {
+/// A set of tokens issued by Ring.
+///
+/// These are typically only needed when implementing a [`TokenStore`], and can be serialized
+/// (and deserialized) to store them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tokens {
     pub(crate) access_token: String,
     pub(crate) expires_at: DateTime<Utc>,
     pub(crate) refresh_token: String,
 }
 
 impl Tokens {
+    /// Create a set of tokens.
     #[must_use]
     pub const fn new(
         access_token: String,
@@ -34,30 +46,42 @@ impl Tokens {
             refresh_token,
         }
     }
+
+    /// The access token, used to authenticate requests to Ring.
+    #[must_use]
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// When the access token expires.
+    #[must_use]
+    pub const fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    /// The refresh token, used to issue a new set of tokens.
+    #[must_use]
+    pub fn refresh_token(&self) -> &str {
+        &self.refresh_token
+    }
+}
+
+/// The tokens as issued by Ring, which say how long (in seconds) the access token lasts, rather
+/// than when it expires.
+#[derive(Deserialize)]
+struct IssuedTokens {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: String,
 }
 
-impl<'de> serde::Deserialize<'de> for Tokens {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let value = serde_json::Value::deserialize(deserializer)?;
-
-        let access_token = value["access_token"]
-            .as_str()
-            .ok_or_else(|| serde::de::Error::custom("Invalid access token"))?
-            .to_string();
-        let refresh_token = value["refresh_token"]
-            .as_str()
-            .ok_or_else(|| serde::de::Error::custom("Invalid refresh token"))?
-            .to_string();
-        let expires_at = Utc::now().add(chrono::Duration::seconds(
-            value["expires_in"]
-                .as_i64()
-                .ok_or_else(|| serde::de::Error::custom("Invalid expires_in value"))?,
-        ));
-
-        Ok(Self::new(access_token, expires_at, refresh_token))
+impl From<IssuedTokens> for Tokens {
+    fn from(issued: IssuedTokens) -> Self {
+        Self::new(
+            issued.access_token,
+            Utc::now().add(chrono::Duration::seconds(issued.expires_in)),
+            issued.refresh_token,
+        )
     }
 }
 
@@ -65,10 +89,12 @@ impl<'de> serde::Deserialize<'de> for Tokens {
 pub(crate) struct RingAuth {
     client: reqwest::Client,
     operating_system: OperatingSystem,
+    endpoints: Endpoints,
 }
 
 /// A set of credentials used to authenticate with the Ring API.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Credentials {
     /// A username and password.
     ///
@@ -83,14 +109,20 @@ pub enum Credentials {
     /// using [`get_refresh_token`](Client::get_refresh_token) to retrieve it for later
     /// use.
     RefreshToken(String),
+
+    /// Tokens which were previously saved to the [`TokenStore`] configured on the client.
+    ///
+    /// See [`crate::ClientBuilder::token_store`].
+    Stored,
 }
 
 impl RingAuth {
     #[must_use]
-    pub fn new(operating_system: OperatingSystem) -> Self {
+    pub fn new(operating_system: OperatingSystem, endpoints: Endpoints) -> Self {
         Self {
             client: reqwest::Client::new(),
             operating_system,
+            endpoints,
         }
     }
 
@@ -102,7 +134,7 @@ impl RingAuth {
     ) -> Result<Tokens, AuthenticationError> {
         let response = self
             .client
-            .post(helper::url::get_base_url(&Url::Oauth))
+            .post(self.endpoints.get_base_url(&Url::Oauth))
             .header("User-Agent", self.operating_system.get_user_agent())
             .header("2fa-support", "true")
             .header(
@@ -128,7 +160,7 @@ impl RingAuth {
             return Err(AuthenticationError::InvalidCredentials);
         }
 
-        Ok(response.json::<Tokens>().await?)
+        Ok(response.json::<IssuedTokens>().await?.into())
     }
 
     pub(crate) async fn respond_to_challenge(
@@ -140,7 +172,7 @@ impl RingAuth {
     ) -> Result<Tokens, AuthenticationError> {
         Ok(self
             .client
-            .post(helper::url::get_base_url(&Url::Oauth))
+            .post(self.endpoints.get_base_url(&Url::Oauth))
             .header("User-Agent", self.operating_system.get_user_agent())
             .header("2fa-support", "true")
             .header("2fa-code", code)
@@ -157,8 +189,9 @@ impl RingAuth {
             }))
             .send()
             .await?
-            .json::<Tokens>()
-            .await?)
+            .json::<IssuedTokens>()
+            .await?
+            .into())
     }
 
     pub(crate) async fn refresh_tokens(
@@ -167,7 +200,7 @@ impl RingAuth {
     ) -> Result<Tokens, AuthenticationError> {
         Ok(self
             .client
-            .post(helper::url::get_base_url(&Url::Oauth))
+            .post(self.endpoints.get_base_url(&Url::Oauth))
             .header("User-Agent", self.operating_system.get_user_agent())
             .header("2fa-support", "true")
             .json(&json!({
@@ -178,38 +211,21 @@ impl RingAuth {
             }))
             .send()
             .await?
-            .json::<Tokens>()
-            .await?)
+            .json::<IssuedTokens>()
+            .await?
+            .into())
     }
 }
 
 impl Client {
-    pub(crate) async fn refresh_tokens_if_needed(
-        &self,
-    ) -> Result<Arc<Tokens>, AuthenticationError> {
-        let mut token_to_refresh = self
-            .tokens
-            .write()
-            .await
-            .take_if(|current_tokens| current_tokens.expires_at < Utc::now());
-
-        if let Some(current_tokens) = &token_to_refresh {
-            let replacement_tokens =
-                Arc::new(self.auth.refresh_tokens(Arc::clone(current_tokens)).await?);
-
-            token_to_refresh.replace(Arc::clone(&replacement_tokens));
-
-            log::info!(
-                "Tokens have been replaced successfully. New expiration time: {}",
-                replacement_tokens.expires_at,
-            );
+    /// Replace the tokens used by the client.
+    ///
+    /// See [`TokenManager::replace`].
+    pub(crate) async fn store_tokens(&self, tokens: Tokens) -> Arc<Tokens> {
+        let tokens = self.inner.tokens.replace(tokens).await;
 
-            return Ok(Arc::clone(&replacement_tokens));
-        }
+        self.inner.tokens.start_background_refresh();
 
-        self.tokens.read().await.as_ref().map_or_else(
-            || Err(AuthenticationError::InvalidCredentials),
-            |current_tokens| Ok(Arc::clone(current_tokens)),
-        )
+        tokens
     }
 }
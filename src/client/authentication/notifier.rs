@@ -0,0 +1,56 @@
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::sync::RwLock;
+use tokio::sync::watch;
+
+type Callback = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Notifies interested parties whenever Ring issues a new refresh token.
+pub struct RefreshTokenNotifier {
+    callbacks: RwLock<Vec<Callback>>,
+    sender: watch::Sender<Option<String>>,
+}
+
+impl RefreshTokenNotifier {
+    pub fn new() -> Self {
+        Self {
+            callbacks: RwLock::new(Vec::new()),
+            sender: watch::Sender::new(None),
+        }
+    }
+
+    /// Register a callback which is called with every new refresh token.
+    pub fn on_change(&self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        self.callbacks
+            .write()
+            .expect("Refresh token callbacks should not be poisoned")
+            .push(Box::new(callback));
+    }
+
+    /// Subscribe to the current refresh token.
+    pub fn subscribe(&self) -> watch::Receiver<Option<String>> {
+        self.sender.subscribe()
+    }
+
+    /// Notify every callback and subscriber of a new refresh token.
+    pub fn notify(&self, refresh_token: &str) {
+        self.sender.send_replace(Some(refresh_token.to_string()));
+
+        for callback in self
+            .callbacks
+            .read()
+            .expect("Refresh token callbacks should not be poisoned")
+            .iter()
+        {
+            callback(refresh_token);
+        }
+    }
+}
+
+impl Debug for RefreshTokenNotifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefreshTokenNotifier")
+            .field("subscribers", &self.sender.receiver_count())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,235 @@
+use crate::client::authentication::Tokens;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Errors which can occur when loading or saving tokens using a [`TokenStore`].
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum TokenStoreError {
+    /// Reading from, or writing to, the underlying storage failed.
+    #[error("An error occurred while accessing the token store: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The stored tokens could not be encoded or decoded.
+    #[error("An error occurred while encoding or decoding the stored tokens: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+
+    /// A custom token store failed for another reason.
+    #[error("An error occurred in the token store: {0}")]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Persistent storage for the tokens issued by Ring.
+///
+/// Ring rotates the refresh token every time the tokens are refreshed, which means a refresh
+/// token captured once will quickly become stale. When a store is configured (using
+/// [`crate::ClientBuilder::token_store`]), the client will save the tokens whenever they're
+/// issued - on login, when responding to a challenge, and on every refresh.
+///
+/// The stored tokens can be used to log in again using [`super::Credentials::Stored`], meaning
+/// long-running services can survive restarts without completing the full login flow again.
+///
+/// The store is called on Tokio's blocking thread pool, so its methods may block (for example,
+/// on file or network I/O).
+pub trait TokenStore: Debug + Send + Sync {
+    /// Load the previously saved tokens, if there are any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokens exist, but could not be loaded.
+    fn load(&self) -> Result<Option<Tokens>, TokenStoreError>;
+
+    /// Save a newly issued set of tokens, replacing any which were saved previously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokens could not be saved.
+    fn save(&self, tokens: &Tokens) -> Result<(), TokenStoreError>;
+}
+
+/// A [`TokenStore`] which saves tokens to a JSON file.
+///
+/// Writes are atomic (the tokens are written to a temporary file which then replaces the
+/// original), and on Unix platforms the file is only readable and writable by its owner
+/// (`0600`).
+///
+/// # Example
+///
+/// ```no_run
+/// use ring_client::Client;
+///
+/// use ring_client::authentication::{Credentials, FileTokenStore};
+/// use ring_client::OperatingSystem;
+///
+/// # tokio_test::block_on(async {
+/// let client = Client::builder("Home Automation", "mock-system-id", OperatingSystem::Ios)
+///     .token_store(FileTokenStore::new("/var/lib/home-automation/ring-tokens.json"))
+///     .build();
+///
+/// // Log in using the tokens saved by a previous run.
+/// client.login(Credentials::Stored)
+///     .await
+///     .expect("Logging in with stored tokens should not fail");
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Create a store which saves tokens to the given file.
+    ///
+    /// The file (but not its parent directory) is created when the tokens are first saved.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The path of the file the tokens are saved to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The path of the temporary file used while writing tokens.
+    fn temporary_path(&self) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".tmp");
+
+        self.path.with_file_name(file_name)
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> Result<Option<Tokens>, TokenStoreError> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Some(serde_json::from_slice(&contents)?))
+    }
+
+    fn save(&self, tokens: &Tokens) -> Result<(), TokenStoreError> {
+        let contents = serde_json::to_vec(tokens)?;
+
+        let temporary_path = self.temporary_path();
+
+        // A temporary file left behind by an earlier failure may have broader permissions, so
+        // it's removed rather than reused.
+        match fs::remove_file(&temporary_path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(&temporary_path)?;
+
+        file.write_all(&contents)?;
+        file.sync_all()?;
+
+        fs::rename(&temporary_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn store(name: &str) -> FileTokenStore {
+        let path =
+            std::env::temp_dir().join(format!("ring-client-{name}-{}.json", std::process::id()));
+
+        let _ = fs::remove_file(&path);
+
+        FileTokenStore::new(path)
+    }
+
+    #[test]
+    fn test_loading_missing_tokens() {
+        let store = store("missing");
+
+        assert!(store.load().expect("Loading should not fail").is_none());
+    }
+
+    #[test]
+    fn test_saving_and_loading_tokens() {
+        let store = store("round-trip");
+
+        let expires_at = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .expect("Timestamp should be valid")
+            .with_timezone(&Utc);
+
+        store
+            .save(&Tokens::new(
+                "access-token".to_string(),
+                expires_at,
+                "refresh-token".to_string(),
+            ))
+            .expect("Saving should not fail");
+
+        let tokens = store
+            .load()
+            .expect("Loading should not fail")
+            .expect("Tokens should have been saved");
+
+        assert_eq!(tokens.access_token(), "access-token");
+        assert_eq!(tokens.refresh_token(), "refresh-token");
+        assert_eq!(tokens.expires_at(), expires_at);
+        assert!(!store.temporary_path().exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let permissions = fs::metadata(store.path())
+                .expect("Tokens file should exist")
+                .permissions();
+
+            assert_eq!(permissions.mode() & 0o777, 0o600);
+        }
+
+        fs::remove_file(store.path()).expect("Tokens file should be removable");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replacing_stale_temporary_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let store = store("stale");
+
+        fs::write(store.temporary_path(), b"stale").expect("Stale file should be writable");
+        fs::set_permissions(store.temporary_path(), fs::Permissions::from_mode(0o644))
+            .expect("Stale file permissions should be settable");
+
+        store
+            .save(&Tokens::new(
+                "access-token".to_string(),
+                Utc::now(),
+                "refresh-token".to_string(),
+            ))
+            .expect("Saving should not fail");
+
+        let permissions = fs::metadata(store.path())
+            .expect("Tokens file should exist")
+            .permissions();
+
+        assert_eq!(permissions.mode() & 0o777, 0o600);
+
+        fs::remove_file(store.path()).expect("Tokens file should be removable");
+    }
+}
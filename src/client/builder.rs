@@ -0,0 +1,147 @@
+use crate::client::api::{RetryPolicy, RingApi};
+use crate::client::authentication::{RingAuth, TokenManager, TokenStore};
+use crate::client::{Client, ClientInner};
+use crate::helper::OperatingSystem;
+use crate::helper::url::Endpoints;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// By default, tokens are refreshed a minute before they expire.
+const DEFAULT_REFRESH_SKEW: Duration = Duration::from_secs(60);
+
+/// By default, requests sent over a WebSocket time out after ten seconds.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builder used to configure a [`Client`] before it is created.
+///
+/// # Example
+///
+/// ```
+/// use ring_client::{Client, Endpoints};
+/// use ring_client::OperatingSystem;
+///
+/// let client = Client::builder("Home Automation", "mock-system-id", OperatingSystem::Ios)
+///     .endpoints(Endpoints::from_host("127.0.0.1:8080"))
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct ClientBuilder {
+    display_name: String,
+    system_id: String,
+    operating_system: OperatingSystem,
+    endpoints: Endpoints,
+    store: Option<Arc<dyn TokenStore>>,
+    refresh_skew: Duration,
+    background_refresh: bool,
+    retry_policy: RetryPolicy,
+    request_timeout: Duration,
+}
+
+impl ClientBuilder {
+    pub(crate) fn new(
+        display_name: &str,
+        system_id: &str,
+        operating_system: OperatingSystem,
+    ) -> Self {
+        Self {
+            display_name: display_name.to_string(),
+            system_id: system_id.to_string(),
+            operating_system,
+            endpoints: Endpoints::default(),
+            store: None,
+            refresh_skew: DEFAULT_REFRESH_SKEW,
+            background_refresh: false,
+            retry_policy: RetryPolicy::default(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Override the base URLs used to communicate with Ring.
+    ///
+    /// By default, the production Ring API is used.
+    #[must_use]
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Persist the tokens issued by Ring using a [`TokenStore`].
+    ///
+    /// The tokens are saved on login, when responding to a challenge, and every time they're
+    /// refreshed. They can be used to log in again using
+    /// [`Credentials::Stored`](crate::authentication::Credentials::Stored).
+    #[must_use]
+    pub fn token_store(mut self, store: impl TokenStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    /// How long before the access token expires that it should be refreshed.
+    ///
+    /// Refreshing early avoids sending a request to Ring with a token which expires while the
+    /// request is in flight. Defaults to one minute, and is capped at one day.
+    #[must_use]
+    pub const fn refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+
+    /// Refresh the tokens ahead of expiry using a background task, rather than only when the
+    /// next request is made.
+    ///
+    /// The task is started on the Tokio runtime once the client has logged in, and is stopped
+    /// when the client is dropped. Disabled by default.
+    #[must_use]
+    pub const fn background_refresh(mut self, enabled: bool) -> Self {
+        self.background_refresh = enabled;
+        self
+    }
+
+    /// Retry requests to the Ring REST API which fail with a transient error, according to the
+    /// given [`RetryPolicy`].
+    ///
+    /// By default, requests are not retried.
+    #[must_use]
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// How long to wait for Ring to respond to a request sent over a WebSocket (see
+    /// [`Connection::request`](crate::location::Connection::request)).
+    ///
+    /// Defaults to ten seconds.
+    #[must_use]
+    pub const fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Create the client.
+    #[must_use]
+    pub fn build(self) -> Client {
+        let tokens = Arc::new(TokenManager::new(
+            RingAuth::new(self.operating_system, self.endpoints.clone()),
+            self.store,
+            self.refresh_skew,
+            self.background_refresh,
+        ));
+
+        Client {
+            inner: Arc::new(ClientInner {
+                user: RwLock::new(None),
+                api: RingApi::new(
+                    self.operating_system,
+                    self.endpoints,
+                    Arc::clone(&tokens),
+                    self.retry_policy,
+                ),
+                tokens,
+                display_name: self.display_name,
+                system_id: self.system_id,
+                request_timeout: self.request_timeout,
+            }),
+        }
+    }
+}
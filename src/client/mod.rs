@@ -1,31 +1,47 @@
 use crate::client::api::RingApi;
-use crate::client::authentication::{Credentials, RingAuth, Tokens};
+use crate::client::authentication::{Credentials, TokenManager};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 mod api;
+mod builder;
 mod wrapper;
 
 /// Support for the Ring Authentication flow.
 pub mod authentication;
 
+pub use api::capability;
 pub use api::device;
+pub use api::history;
 pub use api::location;
 pub use api::session;
 pub use api::ticket;
 
 pub use api::ApiError;
+pub use api::RetryPolicy;
+pub use api::{ItemError, Listing};
 pub use authentication::AuthenticationError;
+pub use builder::ClientBuilder;
 
 /// Client used to authenticate and interact with Ring.
-#[derive(Debug)]
+///
+/// Clients are cheap to clone, and every clone shares the same session. This means a client
+/// (and any [`location::Location`] or [`location::Listener`] created from it) can be moved
+/// into a spawned task.
+#[derive(Debug, Clone)]
 pub struct Client {
+    inner: Arc<ClientInner>,
+}
+
+#[derive(Debug)]
+struct ClientInner {
     user: RwLock<Option<Credentials>>,
-    tokens: RwLock<Option<Arc<Tokens>>>,
-    auth: RingAuth,
+    tokens: Arc<TokenManager>,
     api: RingApi,
     display_name: String,
     system_id: String,
+    request_timeout: Duration,
 }
 
 impl Client {
@@ -39,13 +55,19 @@ impl Client {
         system_id: &str,
         operating_system: crate::helper::OperatingSystem,
     ) -> Self {
-        Self {
-            user: RwLock::new(None),
-            tokens: RwLock::new(None),
-            auth: RingAuth::new(operating_system),
-            api: RingApi::new(operating_system),
-            display_name: display_name.to_string(),
-            system_id: system_id.to_string(),
-        }
+        Self::builder(display_name, system_id, operating_system).build()
+    }
+
+    /// Create a builder for a new client.
+    ///
+    /// This can be used to configure the client further before it is created, such as
+    /// overriding the endpoints used to communicate with Ring (see [`ClientBuilder`]).
+    #[must_use]
+    pub fn builder(
+        display_name: &str,
+        system_id: &str,
+        operating_system: crate::helper::OperatingSystem,
+    ) -> ClientBuilder {
+        ClientBuilder::new(display_name, system_id, operating_system)
     }
 }
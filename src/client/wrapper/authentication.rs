@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use chrono::DateTime;
+use tokio::sync::watch;
 
 use crate::Client;
 use crate::authentication::{AuthenticationError, Credentials};
@@ -12,6 +13,7 @@ impl Client {
     /// These credentials can either be:
     /// * A username and password ([`Credentials::User`])
     /// * A refresh token ([`Credentials::RefreshToken`])
+    /// * Tokens saved by a previous session ([`Credentials::Stored`])
     ///
     /// # Example
     ///
@@ -72,37 +74,36 @@ impl Client {
     /// Returns an error logging in was unsuccessful and a Two Factor Authentication (2FA)
     /// challenge was not issued.
     pub async fn login(&self, credentials: Credentials) -> Result<(), AuthenticationError> {
-        let mut lock = self.user.write().await;
-        let user = lock.insert(credentials);
-
-        match user {
+        let tokens = match &credentials {
             Credentials::User { username, password } => {
-                self.tokens.write().await.replace(Arc::new(
-                    self.auth.login(username, password, &self.system_id).await?,
-                ));
+                self.inner
+                    .tokens
+                    .auth
+                    .login(username, password, &self.inner.system_id)
+                    .await
             }
-            &mut Credentials::RefreshToken(ref refresh_token) => {
-                self.tokens.write().await.replace(Arc::new(
-                    self.auth
-                        .refresh_tokens(Arc::new(Tokens::new(
-                            String::new(),
-                            DateTime::default(),
-                            refresh_token.clone(),
-                        )))
-                        .await?,
-                ));
+            Credentials::RefreshToken(refresh_token) => {
+                self.inner
+                    .tokens
+                    .auth
+                    .refresh_tokens(Arc::new(Tokens::new(
+                        String::new(),
+                        DateTime::default(),
+                        refresh_token.clone(),
+                    )))
+                    .await
             }
-        }
+            Credentials::Stored => self.load_stored_tokens().await,
+        };
 
-        self.api
-            .set_session(
-                &self.display_name,
-                &self.system_id,
-                &*self
-                    .refresh_tokens_if_needed()
-                    .await
-                    .map_err(|_| AuthenticationError::SessionFailed)?,
-            )
+        // The credentials are kept regardless of the outcome, as they're needed to respond
+        // to any challenges issued by Ring.
+        self.inner.user.write().await.replace(credentials);
+        self.store_tokens(tokens?).await;
+
+        self.inner
+            .api
+            .set_session(&self.inner.display_name, &self.inner.system_id)
             .await
             .map_err(|_| AuthenticationError::SessionFailed)?;
 
@@ -117,22 +118,21 @@ impl Client {
     ///
     /// Returns an error if the challenge could not be completed.
     pub async fn respond_to_challenge(&self, code: &str) -> Result<(), AuthenticationError> {
-        if let Some(Credentials::User { username, password }) = self.user.read().await.as_ref() {
-            self.tokens.write().await.replace(Arc::new(
-                self.auth
-                    .respond_to_challenge(username, password, &self.system_id, code)
+        if let Some(Credentials::User { username, password }) =
+            self.inner.user.read().await.as_ref()
+        {
+            self.store_tokens(
+                self.inner
+                    .tokens
+                    .auth
+                    .respond_to_challenge(username, password, &self.inner.system_id, code)
                     .await?,
-            ));
+            )
+            .await;
 
-            self.api
-                .set_session(
-                    &self.display_name,
-                    &self.system_id,
-                    &*self
-                        .refresh_tokens_if_needed()
-                        .await
-                        .map_err(|_| AuthenticationError::SessionFailed)?,
-                )
+            self.inner
+                .api
+                .set_session(&self.inner.display_name, &self.inner.system_id)
                 .await
                 .map_err(|_| AuthenticationError::SessionFailed)?;
         }
@@ -143,12 +143,91 @@ impl Client {
     /// Get the refresh token issued by Ring for the current session.
     ///
     /// If [`Credentials::RefreshToken`] was used to login initially, this will return the
-    /// same token.
+    /// same token until the tokens are next refreshed.
+    ///
+    /// Ring rotates the refresh token every time the tokens are refreshed, so rather than polling
+    /// this, consider configuring a [`TokenStore`](crate::authentication::TokenStore) using
+    /// [`ClientBuilder::token_store`](crate::ClientBuilder::token_store).
     pub async fn get_refresh_token(&self) -> Option<String> {
-        if let Some(refresh_token) = self.tokens.read().await.as_ref() {
+        if let Some(refresh_token) = self.inner.tokens.current().await {
             return Some(refresh_token.refresh_token.clone());
         }
 
         None
     }
+
+    /// Register a callback which is called whenever Ring issues a new refresh token.
+    ///
+    /// Ring rotates the refresh token every time the tokens are refreshed (and the previous
+    /// refresh token is invalidated), so this can be used to capture the new token immediately -
+    /// for example, to save it in a secrets manager.
+    ///
+    /// The callback is called on login, when responding to a challenge, and every time the
+    /// tokens are refreshed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// client.on_token_refresh(|refresh_token| {
+    ///     println!("Ring issued a new refresh token: {refresh_token}");
+    /// });
+    ///
+    /// let refresh_token = Credentials::RefreshToken("".to_string());
+    ///
+    /// client.login(refresh_token)
+    ///      .await
+    ///      .expect("Logging in with a valid refresh token should not fail");
+    /// # });
+    /// ```
+    pub fn on_token_refresh(&self, callback: impl Fn(&str) + Send + Sync + 'static) {
+        self.inner.tokens.notifier().on_change(callback);
+    }
+
+    /// Watch the refresh token issued by Ring for the current session.
+    ///
+    /// The receiver holds the latest refresh token (or `None` if the client hasn't logged in
+    /// yet), and is notified whenever a new refresh token is issued. See
+    /// [`Client::on_token_refresh`] for when this happens.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ring_client::Client;
+    ///
+    /// use ring_client::authentication::Credentials;
+    /// use ring_client::OperatingSystem;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let client = Client::new("Home Automation", "mock-system-id", OperatingSystem::Ios);
+    ///
+    /// let mut refresh_tokens = client.watch_refresh_token();
+    ///
+    /// tokio::spawn(async move {
+    ///     while refresh_tokens.changed().await.is_ok() {
+    ///         println!("Ring issued a new refresh token: {:?}", *refresh_tokens.borrow());
+    ///     }
+    /// });
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn watch_refresh_token(&self) -> watch::Receiver<Option<String>> {
+        self.inner.tokens.notifier().subscribe()
+    }
+
+    /// Load the tokens saved in the configured token store.
+    async fn load_stored_tokens(&self) -> Result<Tokens, AuthenticationError> {
+        self.inner
+            .tokens
+            .load_stored()
+            .await?
+            .ok_or(AuthenticationError::NoStoredTokens)
+    }
 }
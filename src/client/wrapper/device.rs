@@ -1,10 +1,13 @@
 use crate::Client;
-use crate::client::api::ApiError;
 use crate::client::api::device::Device;
+use crate::client::api::{ApiError, Listing};
 
 impl Client {
     /// Retrieve a list of devices in the Ring account.
     ///
+    /// Devices which could not be decoded are skipped (and logged). Use
+    /// [`Client::get_device_listing`] to find out which devices were skipped.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -39,14 +42,26 @@ impl Client {
     /// Returns an error if the API request fails. This may occur either as the result of an API
     /// error, or if the authentication token needs to be refreshed and it is not successful.
     pub async fn get_devices(&self) -> Result<Vec<Device>, ApiError> {
-        self.api
-            .get_device_data(
-                &*self
-                    .refresh_tokens_if_needed()
-                    .await
-                    .map_err(ApiError::AuthenticationRefreshFailed)?,
-            )
-            .await
-            .map(|data| data.into_iter().map(Device::new).collect())
+        Ok(self.get_device_listing().await?.items)
+    }
+
+    /// Retrieve a list of devices in the Ring account, along with any devices which could not
+    /// be decoded.
+    ///
+    /// Each device is decoded separately, so a single device which can't be decoded (for
+    /// example, because it is missing a field) doesn't prevent the others from being returned.
+    /// The raw JSON of each device which could not be decoded is kept in its [`crate::ItemError`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails. This may occur either as the result of an API
+    /// error, or if the authentication token needs to be refreshed and it is not successful.
+    pub async fn get_device_listing(&self) -> Result<Listing<Device>, ApiError> {
+        Ok(self
+            .inner
+            .api
+            .get_device_data()
+            .await?
+            .map(|data| Device::new(self.clone(), data)))
     }
 }
@@ -1,10 +1,13 @@
-use crate::Client;
 use crate::client::api;
 use crate::client::location::Location;
+use crate::{Client, Listing};
 
 impl Client {
     /// Retrieve a list of locations in the Ring account.
     ///
+    /// Locations which could not be decoded are skipped (and logged). Use
+    /// [`Client::get_location_listing`] to find out which locations were skipped.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -40,18 +43,26 @@ impl Client {
     ///
     /// Returns an error if the API request fails. This may occur either as the result of an API
     /// error, or if the authentication token needs to be refreshed and it is not successful.
-    pub async fn get_locations(&self) -> Result<Vec<Location<'_>>, api::ApiError> {
+    pub async fn get_locations(&self) -> Result<Vec<Location>, api::ApiError> {
+        Ok(self.get_location_listing().await?.items)
+    }
+
+    /// Retrieve a list of locations in the Ring account, along with any locations which could
+    /// not be decoded.
+    ///
+    /// The raw JSON of each location which could not be decoded is kept in its
+    /// [`crate::ItemError`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails. This may occur either as the result of an API
+    /// error, or if the authentication token needs to be refreshed and it is not successful.
+    pub async fn get_location_listing(&self) -> Result<Listing<Location>, api::ApiError> {
         Ok(self
+            .inner
             .api
-            .get_location_data(
-                &*self
-                    .refresh_tokens_if_needed()
-                    .await
-                    .map_err(api::ApiError::AuthenticationRefreshFailed)?,
-            )
+            .get_location_data()
             .await?
-            .into_iter()
-            .map(|data| Location::new(self, data))
-            .collect())
+            .map(|data| Location::new(self.clone(), data)))
     }
 }
@@ -2,27 +2,139 @@ const CLIENT_API_BASE_URL: &str = "https://api.ring.com/clients_api";
 const DEVICE_API_BASE_URL: &str = "https://api.ring.com/devices/v1";
 const OAUTH_BASE_URL: &str = "https://oauth.ring.com/oauth/token";
 const APP_API_BASE_URL: &str = "https://prd-api-us.prd.rings.solutions/api/v1";
+const WEBSOCKET_SCHEME: &str = "wss";
 
 /// A supported route for the Ring API.
 pub enum Url<'a> {
     Oauth,
     Session,
     Devices,
+    DeviceHistory { device_id: u64 },
+    LocationEvents { location_id: &'a str },
+    Recording { ding_id: u64 },
+    Snapshot { device_id: u64 },
+    SnapshotTimestamps,
+    SnapshotUpdate,
+    Light { device_id: u64, on: bool },
+    Siren { device_id: u64, on: bool },
+    ChimeSound { device_id: u64 },
     Locations,
     Ticket,
     Websocket { host: &'a str, auth_code: &'a str },
 }
 
-/// Get a base URL for a given route.
-pub fn get_base_url(url: &Url<'_>) -> String {
-    match url {
-        Url::Oauth => OAUTH_BASE_URL.into(),
-        Url::Session => format!("{CLIENT_API_BASE_URL}/session"),
-        Url::Devices => format!("{CLIENT_API_BASE_URL}/ring_devices"),
-        Url::Locations => format!("{DEVICE_API_BASE_URL}/locations"),
-        Url::Ticket => format!("{APP_API_BASE_URL}/clap/tickets"),
-        Url::Websocket { host, auth_code } => {
-            format!("wss://{host}/ws?authcode={auth_code}&ack=false&transport=websocket",)
+/// The set of base URLs used to communicate with Ring.
+///
+/// By default, these point at the production Ring API. They can be overridden (using
+/// [`crate::ClientBuilder::endpoints`]) to target a different server, such as a local
+/// stand-in used for testing.
+///
+/// New endpoints may be added over time, so rather than being built field by field, endpoints
+/// are created using [`Endpoints::default`] or [`Endpoints::from_host`], and then adjusted.
+///
+/// # Example
+///
+/// ```
+/// use ring_client::Endpoints;
+///
+/// let mut endpoints = Endpoints::from_host("127.0.0.1:8080");
+///
+/// endpoints.websocket_host = Some("127.0.0.1:8081".to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Endpoints {
+    /// The OAuth token endpoint.
+    pub oauth: String,
+
+    /// The base URL of the Ring Clients API (`clients_api`).
+    pub client_api: String,
+
+    /// The base URL of the Ring Devices API (`devices/v1`).
+    pub device_api: String,
+
+    /// The base URL of the Ring App API, which issues tickets for WebSocket connections.
+    pub app_api: String,
+
+    /// The scheme used when opening WebSocket connections (i.e. `wss` or `ws`).
+    pub websocket_scheme: String,
+
+    /// The host used when opening WebSocket connections.
+    ///
+    /// When not set, the host issued by Ring as part of the ticket is used.
+    pub websocket_host: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            oauth: OAUTH_BASE_URL.to_string(),
+            client_api: CLIENT_API_BASE_URL.to_string(),
+            device_api: DEVICE_API_BASE_URL.to_string(),
+            app_api: APP_API_BASE_URL.to_string(),
+            websocket_scheme: WEBSOCKET_SCHEME.to_string(),
+            websocket_host: None,
+        }
+    }
+}
+
+impl Endpoints {
+    /// Point every endpoint at a single server (such as a local stand-in used for testing),
+    /// which is reached over plain HTTP.
+    #[must_use]
+    pub fn from_host(host: &str) -> Self {
+        let base = format!("http://{host}");
+
+        Self {
+            oauth: format!("{base}/oauth/token"),
+            client_api: format!("{base}/clients_api"),
+            device_api: format!("{base}/devices/v1"),
+            app_api: format!("{base}/api/v1"),
+            websocket_scheme: "ws".to_string(),
+            websocket_host: Some(host.to_string()),
+        }
+    }
+
+    /// Get a base URL for a given route.
+    pub(crate) fn get_base_url(&self, url: &Url<'_>) -> String {
+        match url {
+            Url::Oauth => self.oauth.clone(),
+            Url::Session => format!("{}/session", self.client_api),
+            Url::Devices => format!("{}/ring_devices", self.client_api),
+            Url::DeviceHistory { device_id } => {
+                format!("{}/doorbots/{device_id}/history", self.client_api)
+            }
+            Url::LocationEvents { location_id } => {
+                format!("{}/locations/{location_id}/events", self.client_api)
+            }
+            Url::Recording { ding_id } => format!("{}/dings/{ding_id}/recording", self.client_api),
+            Url::Snapshot { device_id } => {
+                format!("{}/snapshots/image/{device_id}", self.client_api)
+            }
+            Url::SnapshotTimestamps => format!("{}/snapshots/timestamps", self.client_api),
+            Url::SnapshotUpdate => format!("{}/snapshots/update_all", self.client_api),
+            Url::Light { device_id, on } => format!(
+                "{}/doorbots/{device_id}/floodlight_light_{}",
+                self.client_api,
+                if *on { "on" } else { "off" }
+            ),
+            Url::Siren { device_id, on } => format!(
+                "{}/doorbots/{device_id}/siren_{}",
+                self.client_api,
+                if *on { "on" } else { "off" }
+            ),
+            Url::ChimeSound { device_id } => {
+                format!("{}/chimes/{device_id}/play_sound", self.client_api)
+            }
+            Url::Locations => format!("{}/locations", self.device_api),
+            Url::Ticket => format!("{}/clap/tickets", self.app_api),
+            Url::Websocket { host, auth_code } => {
+                format!(
+                    "{}://{}/ws?authcode={auth_code}&ack=false&transport=websocket",
+                    self.websocket_scheme,
+                    self.websocket_host.as_deref().unwrap_or(host)
+                )
+            }
         }
     }
 }
@@ -33,29 +145,98 @@ mod tests {
 
     #[test]
     fn test_get_base_url() {
-        assert_eq!(get_base_url(&Url::Oauth), OAUTH_BASE_URL);
+        let endpoints = Endpoints::default();
+
+        assert_eq!(endpoints.get_base_url(&Url::Oauth), OAUTH_BASE_URL);
         assert_eq!(
-            get_base_url(&Url::Session),
+            endpoints.get_base_url(&Url::Session),
             format!("https://api.ring.com/clients_api/session")
         );
         assert_eq!(
-            get_base_url(&Url::Devices),
+            endpoints.get_base_url(&Url::Devices),
             format!("https://api.ring.com/clients_api/ring_devices")
         );
         assert_eq!(
-            get_base_url(&Url::Locations),
+            endpoints.get_base_url(&Url::DeviceHistory { device_id: 12345 }),
+            "https://api.ring.com/clients_api/doorbots/12345/history"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::LocationEvents {
+                location_id: "location-1"
+            }),
+            "https://api.ring.com/clients_api/locations/location-1/events"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Recording { ding_id: 67890 }),
+            "https://api.ring.com/clients_api/dings/67890/recording"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Snapshot { device_id: 12345 }),
+            "https://api.ring.com/clients_api/snapshots/image/12345"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::SnapshotTimestamps),
+            "https://api.ring.com/clients_api/snapshots/timestamps"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Light {
+                device_id: 12345,
+                on: true
+            }),
+            "https://api.ring.com/clients_api/doorbots/12345/floodlight_light_on"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Siren {
+                device_id: 12345,
+                on: false
+            }),
+            "https://api.ring.com/clients_api/doorbots/12345/siren_off"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Locations),
             format!("https://api.ring.com/devices/v1/locations")
         );
         assert_eq!(
-            get_base_url(&Url::Ticket),
+            endpoints.get_base_url(&Url::Ticket),
             format!("https://prd-api-us.prd.rings.solutions/api/v1/clap/tickets")
         );
         assert_eq!(
-            get_base_url(&Url::Websocket {
+            endpoints.get_base_url(&Url::Websocket {
                 host: "example.com",
                 auth_code: "12345"
             }),
             "wss://example.com/ws?authcode=12345&ack=false&transport=websocket"
         );
     }
+
+    #[test]
+    fn test_get_overridden_base_url() {
+        let mut endpoints = Endpoints::from_host("127.0.0.1:8080");
+
+        endpoints.websocket_host = Some("127.0.0.1:8081".to_string());
+
+        assert_eq!(
+            endpoints.get_base_url(&Url::Oauth),
+            "http://127.0.0.1:8080/oauth/token"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Devices),
+            "http://127.0.0.1:8080/clients_api/ring_devices"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Locations),
+            "http://127.0.0.1:8080/devices/v1/locations"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Ticket),
+            "http://127.0.0.1:8080/api/v1/clap/tickets"
+        );
+        assert_eq!(
+            endpoints.get_base_url(&Url::Websocket {
+                host: "example.com",
+                auth_code: "12345"
+            }),
+            "ws://127.0.0.1:8081/ws?authcode=12345&ack=false&transport=websocket"
+        );
+    }
 }
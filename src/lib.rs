@@ -85,8 +85,8 @@
 //!
 //! ### Sending Events
 //!
-//! The [`location::Listener`] can also be used to send events to the Ring API, such as arming or disarming an alarm
-//! system.
+//! The [`location::Listener`] can also be used to send events to the Ring API. Alarm systems can be armed or
+//! disarmed using [`location::Listener::set_alarm_mode`].
 //!
 //! ```no_run
 //! use serde_json::json;
@@ -177,6 +177,9 @@
 //! ```sh
 //! cp .env.example .env
 //! ```
+//!
+//! The remaining tests run against an in-process stand-in for the Ring API, which is available
+//! to downstream crates too by enabling the `testing` feature (see the `testing` module).
 
 //! #### Running tests
 
@@ -189,7 +192,11 @@ mod client;
 mod constant;
 mod helper;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 pub use client::*;
+pub use helper::url::Endpoints;
 
 #[doc(hidden)]
 pub use helper::OperatingSystem;
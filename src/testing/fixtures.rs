@@ -0,0 +1,115 @@
+//! Minimal payloads which match the shape of those returned by Ring.
+
+use serde_json::{Value, json};
+
+/// A minimal location, in the shape returned by `GET /devices/v1/locations`.
+#[must_use]
+pub fn location(id: &str, name: &str) -> Value {
+    json!({
+        "location_id": id,
+        "name": name,
+        "owner_id": 1,
+        "is_owner": true,
+        "is_jobsite": false,
+        "user_verified": true,
+        "geo_service_verified": "verified",
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-01T00:00:00Z",
+        "geo_coordinates": {
+            "latitude": 51.5072,
+            "longitude": -0.1276
+        },
+        "address": {
+            "address1": "1 Mock Street",
+            "address2": "",
+            "city": "London",
+            "country": "GB",
+            "cross_street": "",
+            "state": "London",
+            "timezone": "Europe/London",
+            "zip_code": "SW1A 1AA"
+        }
+    })
+}
+
+/// A minimal device, in the shape returned by `GET /clients_api/ring_devices`.
+#[must_use]
+pub fn device(kind: &str, id: u64, location_id: &str, description: &str) -> Value {
+    json!({
+        "id": id,
+        "kind": kind,
+        "location_id": location_id,
+        "description": description,
+        "device_id": format!("mock-device-{id}"),
+        "firmware_version": "Up to Date",
+        "battery_life": null,
+        "alerts": {
+            "connection": "online"
+        },
+        "features": {},
+        "settings": {}
+    })
+}
+
+/// A minimal camera (or doorbell), in the shape returned by `GET /clients_api/ring_devices`.
+///
+/// Unlike [`device`], Ring sends the features and settings of its recordings and motion
+/// detection.
+#[must_use]
+pub fn camera(kind: &str, id: u64, location_id: &str, description: &str) -> Value {
+    let mut camera = device(kind, id, location_id, description);
+
+    camera["features"]["show_recordings"] = json!(true);
+    camera["settings"]["motion_detection_enabled"] = json!(true);
+
+    camera
+}
+
+/// A minimal alarm device, in the shape of a `DeviceInfoDocGetList` document.
+///
+/// Security panels (`security-panel`) start disarmed.
+///
+/// The device type is the type Ring uses for the device (i.e. `sensor.contact`).
+#[must_use]
+pub fn alarm_device(device_type: &str, zid: &str, name: &str) -> Value {
+    json!({
+        "general": {
+            "v2": {
+                "zid": zid,
+                "name": name,
+                "deviceType": device_type,
+                "batteryLevel": 100,
+                "tamperStatus": "ok",
+                "commStatus": "ok"
+            }
+        },
+        "device": {
+            "v1": if device_type == "security-panel" {
+                json!({ "mode": "none" })
+            } else {
+                json!({ "faulted": false })
+            }
+        }
+    })
+}
+
+/// A minimal history item, in the shape returned by `GET /clients_api/doorbots/{id}/history`.
+///
+/// The kind is the kind Ring uses for the item (i.e. `ding`, `motion` or `on_demand`).
+#[must_use]
+pub fn history_item(id: u64, kind: &str, device_id: u64) -> Value {
+    json!({
+        "id": id,
+        "kind": kind,
+        "created_at": "2024-01-01T00:00:00.000Z",
+        "answered": false,
+        "favorite": false,
+        "recording": {
+            "status": "ready"
+        },
+        "doorbot": {
+            "id": device_id,
+            "description": format!("Mock Device {device_id}")
+        }
+    })
+}
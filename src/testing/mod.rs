@@ -0,0 +1,395 @@
+//! An in-process stand-in for the Ring API.
+//!
+//! The [`MockRing`] server implements enough of the Ring REST and WebSocket APIs to exercise
+//! the crate (and any code built on top of it) without a Ring account or network access.
+//!
+//! # Example
+//!
+//! ```
+//! use ring_client::authentication::Credentials;
+//! use ring_client::testing::{MockRing, fixtures};
+//!
+//! # tokio_test::block_on(async {
+//! let mock = MockRing::start().await;
+//!
+//! mock.add_user("username", "password");
+//! mock.add_location(fixtures::location("mock-location-id", "Home"));
+//! mock.add_device(
+//!     "stickup_cams",
+//!     fixtures::device("cocoa_camera", 1, "mock-location-id", "Front Garden"),
+//! );
+//!
+//! let client = mock.client();
+//!
+//! client
+//!     .login(Credentials::User {
+//!         username: "username".to_string(),
+//!         password: "password".to_string(),
+//!     })
+//!     .await
+//!     .expect("Logging in with valid credentials should not fail");
+//!
+//! let devices = client.get_devices()
+//!     .await
+//!     .expect("Getting devices should not fail");
+//!
+//! assert_eq!(devices.len(), 1);
+//! # });
+//! ```
+
+pub mod fixtures;
+mod routes;
+mod state;
+mod websocket;
+
+use crate::helper::OperatingSystem;
+use crate::helper::url::Endpoints;
+use crate::{Client, ClientBuilder};
+use serde_json::{Value, json};
+use state::{ScriptedResponse, State};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// A scriptable, in-process stand-in for the Ring API.
+///
+/// The server listens on a random local port, and is shut down when dropped.
+///
+/// It implements:
+/// * `POST /oauth/token` - including the `412` MFA challenge flow.
+/// * `POST /clients_api/session`
+/// * `GET /clients_api/ring_devices`
+/// * `GET /clients_api/doorbots/{id}/history` and `GET /clients_api/locations/{id}/events` -
+///   for the history added using [`MockRing::add_history_item`].
+/// * `GET /clients_api/dings/{id}/recording` - which links to `GET /recordings/{id}`, where
+///   the recordings added using [`MockRing::add_recording`] can be downloaded (including with
+///   a `Range` header).
+/// * `GET /clients_api/snapshots/image/{id}`, `POST /clients_api/snapshots/timestamps` and
+///   `PUT /clients_api/snapshots/update_all` - for the snapshots added using
+///   [`MockRing::add_snapshot`].
+/// * `PUT /clients_api/doorbots/{id}/floodlight_light_on` (and `_off`) and
+///   `PUT /clients_api/doorbots/{id}/siren_on` (and `siren_off`) - which update the
+///   `led_status` and `siren_status` of the device added using [`MockRing::add_device`].
+/// * `POST /clients_api/chimes/{id}/play_sound`
+/// * `GET /devices/v1/locations`
+/// * `GET /api/v1/clap/tickets`
+/// * `GET /ws` - a WebSocket which emits `SessionInfo` and `SubscriptionTopicsInfo` messages
+///   on connection, followed by any messages pushed using [`MockRing::push_message`]. It also
+///   responds to `DeviceInfoDocGetList` messages with the devices added using
+///   [`MockRing::add_alarm_device`], and to `security-panel.switch-mode` commands.
+#[derive(Debug)]
+pub struct MockRing {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    server: JoinHandle<()>,
+}
+
+impl MockRing {
+    /// Start a new server on a random local port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a local port cannot be bound.
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Binding to a local port should not fail");
+
+        let address = listener
+            .local_addr()
+            .expect("A bound listener should have a local address");
+
+        let state = Arc::new(Mutex::new(State::new(address)));
+
+        let router = routes::router(Arc::clone(&state));
+
+        let server = tokio::spawn(async move {
+            if let Err(error) = axum::serve(listener, router).await {
+                log::error!("Mock Ring server stopped unexpectedly: {error:?}");
+            }
+        });
+
+        Self {
+            address,
+            state,
+            server,
+        }
+    }
+
+    /// The address the server is listening on.
+    #[must_use]
+    pub const fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The endpoints which point a [`Client`] at this server.
+    #[must_use]
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints::from_host(&self.address.to_string())
+    }
+
+    /// A [`ClientBuilder`] which is already pointed at this server.
+    #[must_use]
+    pub fn client_builder(&self) -> ClientBuilder {
+        Client::builder("Mock Ring Client", "mock-system-id", OperatingSystem::Ios)
+            .endpoints(self.endpoints())
+    }
+
+    /// A [`Client`] which is pointed at this server.
+    #[must_use]
+    pub fn client(&self) -> Client {
+        self.client_builder().build()
+    }
+
+    /// Register a user which can log in with a username and password.
+    pub fn add_user(&self, username: &str, password: &str) {
+        self.state()
+            .users
+            .insert(username.to_string(), password.to_string());
+    }
+
+    /// Require an MFA (2FA) code when logging in with a username and password.
+    ///
+    /// Login attempts without the code will receive a `412 Precondition Failed` response.
+    pub fn require_mfa(&self, code: &str) {
+        self.state().mfa_code = Some(code.to_string());
+    }
+
+    /// Issue a refresh token which can be used to log in.
+    pub fn add_refresh_token(&self, refresh_token: &str) {
+        self.state()
+            .refresh_tokens
+            .insert(refresh_token.to_string());
+    }
+
+    /// The refresh tokens which are currently valid.
+    ///
+    /// Like Ring, refresh tokens are rotated on use, so a refresh token is invalidated as soon
+    /// as it is exchanged for a new set of tokens.
+    #[must_use]
+    pub fn refresh_tokens(&self) -> Vec<String> {
+        self.state().refresh_tokens.iter().cloned().collect()
+    }
+
+    /// Set the lifetime (in seconds) of newly issued access tokens.
+    pub fn set_token_lifetime(&self, seconds: i64) {
+        self.state().token_lifetime = seconds;
+    }
+
+    /// Revoke every access token issued so far, as Ring does when a session is terminated.
+    ///
+    /// Refresh tokens remain valid, so clients can recover by refreshing their tokens.
+    pub fn revoke_access_tokens(&self) {
+        self.state().access_tokens.clear();
+    }
+
+    /// Add a location to the account.
+    ///
+    /// See [`fixtures::location`] for a minimal location.
+    pub fn add_location(&self, location: Value) {
+        self.state().locations.push(location);
+    }
+
+    /// Add a device to the account.
+    ///
+    /// The category is the list the device is returned in by Ring (i.e. `doorbots`,
+    /// `authorized_doorbots`, `chimes`, `stickup_cams`, `base_stations`, `beams`,
+    /// `beams_bridges` or `other`).
+    ///
+    /// See [`fixtures::device`] for a minimal device.
+    pub fn add_device(&self, category: &str, device: Value) {
+        self.state()
+            .devices
+            .entry(category.to_string())
+            .or_default()
+            .push(device);
+    }
+
+    /// Add an item (such as a ding) to the history of the account.
+    ///
+    /// History is returned newest first (i.e. in descending order of ID), regardless of the
+    /// order items are added in. See [`fixtures::history_item`] for a minimal item.
+    pub fn add_history_item(&self, item: Value) {
+        self.state().history.push(item);
+    }
+
+    /// Add a recording which can be downloaded, for the history item with the given ID.
+    pub fn add_recording(&self, ding_id: u64, recording: Vec<u8>) {
+        self.state().recordings.insert(ding_id, recording);
+    }
+
+    /// Drop the connection part way through the next `count` recording downloads, as happens
+    /// on an unreliable network.
+    pub fn interrupt_recording_downloads(&self, count: usize) {
+        self.state().interrupted_downloads = count;
+    }
+
+    /// Drop the connection part way through the responses to the next `count` requests to the
+    /// given path, as happens on an unreliable network.
+    ///
+    /// The path is relative to the root of the server (i.e. `/clients_api/ring_devices`).
+    pub fn interrupt_responses(&self, path: &str, count: usize) {
+        self.state()
+            .interrupted_responses
+            .insert(path.to_string(), count);
+    }
+
+    /// Set the latest snapshot captured by the camera with the given ID.
+    pub fn add_snapshot(&self, device_id: u64, image: Vec<u8>) {
+        self.state().capture_snapshot(device_id, image);
+    }
+
+    /// Set the snapshot captured the next time a fresh snapshot is requested from the camera
+    /// with the given ID.
+    ///
+    /// Much like Ring, the capture isn't instant, and only completes after the snapshot
+    /// timestamps have been checked once more.
+    pub fn queue_snapshot(&self, device_id: u64, image: Vec<u8>) {
+        self.state().snapshots.entry(device_id).or_default().next = Some(image);
+    }
+
+    /// Add a device to the alarm base station in every location.
+    ///
+    /// Alarm devices are listed over the WebSocket, in response to `DeviceInfoDocGetList`
+    /// messages. See [`fixtures::alarm_device`] for a minimal device.
+    pub fn add_alarm_device(&self, device: Value) {
+        self.state().alarm_devices.push(device);
+    }
+
+    /// Set whether an alarm device is faulted (i.e. a door is open), and push the change to
+    /// every connected WebSocket as a `DataUpdate`.
+    ///
+    /// The alarm cannot be armed while sensors are faulted, unless they are bypassed.
+    pub fn set_alarm_device_faulted(&self, zid: &str, faulted: bool) {
+        for device in &mut self.state().alarm_devices {
+            if device["general"]["v2"]["zid"] == zid {
+                device["device"]["v1"]["faulted"] = faulted.into();
+            }
+        }
+
+        // Like Ring, only the fields which changed are sent.
+        self.push_message(json!({
+            "msg": "DataUpdate",
+            "datatype": "DeviceInfoDocType",
+            "body": [{
+                "general": { "v2": { "zid": zid } },
+                "device": { "v1": { "faulted": faulted } }
+            }]
+        }));
+    }
+
+    /// Abruptly drop the WebSocket after acknowledging the next change to the alarm mode, before
+    /// the change is confirmed (with a `DataUpdate`).
+    pub fn disconnect_before_confirming_alarm_mode(&self) {
+        self.state().disconnect_before_confirming = true;
+    }
+
+    /// The mode of the alarm's security panel, if it has one (i.e. `none`, `some` or `all`).
+    #[must_use]
+    pub fn alarm_mode(&self) -> Option<String> {
+        self.state().alarm_devices.iter().find_map(|device| {
+            device["device"]["v1"]["mode"]
+                .as_str()
+                .map(ToString::to_string)
+        })
+    }
+
+    /// Respond to the next request to the given path with a fixed status and body, instead
+    /// of the usual behaviour.
+    ///
+    /// Scripted responses are consumed in the order they were queued. The path is relative to
+    /// the root of the server (i.e. `/clients_api/ring_devices`).
+    pub fn enqueue_response(&self, path: &str, status: u16, body: Value) {
+        self.enqueue_response_with_headers(path, status, &[], body);
+    }
+
+    /// Like [`MockRing::enqueue_response`], but with additional response headers (i.e.
+    /// `Retry-After`).
+    pub fn enqueue_response_with_headers(
+        &self,
+        path: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: Value,
+    ) {
+        self.state()
+            .scripted
+            .entry(path.to_string())
+            .or_default()
+            .push_back(ScriptedResponse {
+                status,
+                headers: headers
+                    .iter()
+                    .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                    .collect(),
+                body,
+            });
+    }
+
+    /// The number of requests received for the given path.
+    #[must_use]
+    pub fn request_count(&self, path: &str) -> usize {
+        self.state().requests.get(path).copied().unwrap_or_default()
+    }
+
+    /// Push a message to every connected WebSocket.
+    ///
+    /// The message is the body of the event (i.e. `{"msg": "DataUpdate", ...}`).
+    pub fn push_message(&self, message: Value) {
+        // There may not be any connected sockets, in which case the message is simply dropped.
+        let _ = self.state().outbound.send(message);
+    }
+
+    /// Abruptly drop every connected WebSocket, as happens when a Ring broker goes away.
+    pub fn disconnect_websockets(&self) {
+        // There may not be any connected sockets, in which case there is nothing to do.
+        let _ = self.state().disconnects.send(());
+    }
+
+    /// Every message received over a WebSocket so far.
+    #[must_use]
+    pub fn received_messages(&self) -> Vec<Value> {
+        self.state().inbound.clone()
+    }
+
+    /// Wait until at least `count` messages have been received over a WebSocket, and return
+    /// every message received so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the messages are not received within five seconds.
+    pub async fn wait_for_messages(&self, count: usize) -> Vec<Value> {
+        let wait = async {
+            loop {
+                let notify = Arc::clone(&self.state().inbound_notify);
+                let notified = notify.notified();
+
+                let received = self.received_messages();
+
+                if received.len() >= count {
+                    return received;
+                }
+
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(5), wait)
+            .await
+            .expect("Expected messages to be received by the mock Ring server")
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .expect("Mock Ring state should not be poisoned")
+    }
+}
+
+impl Drop for MockRing {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
@@ -0,0 +1,790 @@
+use crate::testing::state::{ScriptedResponse, State};
+use crate::testing::websocket;
+use axum::Json;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::{Path, Query, Request, State as Extract};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+type Shared = Arc<Mutex<State>>;
+
+/// The categories Ring groups devices into in the `ring_devices` response.
+const DEVICE_CATEGORIES: [&str; 8] = [
+    "doorbots",
+    "authorized_doorbots",
+    "chimes",
+    "stickup_cams",
+    "base_stations",
+    "beams",
+    "beams_bridges",
+    "other",
+];
+
+pub fn router(state: Shared) -> Router {
+    Router::new()
+        .route("/oauth/token", post(oauth_token))
+        .route("/clients_api/session", post(session))
+        .route("/clients_api/ring_devices", get(devices))
+        .route("/clients_api/doorbots/{id}/history", get(device_history))
+        .route("/clients_api/locations/{id}/events", get(location_events))
+        .route("/clients_api/dings/{id}/recording", get(recording_url))
+        .route("/recordings/{id}", get(recording))
+        .route("/clients_api/snapshots/image/{id}", get(snapshot))
+        .route(
+            "/clients_api/snapshots/timestamps",
+            post(snapshot_timestamps),
+        )
+        .route("/clients_api/snapshots/update_all", put(update_snapshots))
+        .route(
+            "/clients_api/doorbots/{id}/floodlight_light_on",
+            put(light_on),
+        )
+        .route(
+            "/clients_api/doorbots/{id}/floodlight_light_off",
+            put(light_off),
+        )
+        .route("/clients_api/doorbots/{id}/siren_on", put(siren_on))
+        .route("/clients_api/doorbots/{id}/siren_off", put(siren_off))
+        .route("/clients_api/chimes/{id}/play_sound", post(play_sound))
+        .route("/devices/v1/locations", get(locations))
+        .route("/api/v1/clap/tickets", get(ticket))
+        .route("/ws", get(connect))
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            scripted_response,
+        ))
+        .with_state(state)
+}
+
+fn lock(state: &Shared) -> MutexGuard<'_, State> {
+    state
+        .lock()
+        .expect("Mock Ring state should not be poisoned")
+}
+
+/// Records every request, short-circuits any which have a scripted response queued, and cuts
+/// short any responses which should be interrupted.
+async fn scripted_response(
+    Extract(state): Extract<Shared>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (scripted, interrupted) = {
+        let mut state = lock(&state);
+        let path = request.uri().path();
+
+        (state.record_request(path), state.interrupt_response(path))
+    };
+
+    let response = match scripted {
+        Some(ScriptedResponse {
+            status,
+            headers,
+            body,
+        }) => {
+            let mut response = (
+                StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                Json(body),
+            )
+                .into_response();
+
+            for (name, value) in headers {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.as_bytes()),
+                    HeaderValue::from_str(&value),
+                ) {
+                    response.headers_mut().insert(name, value);
+                }
+            }
+
+            response
+        }
+        None => next.run(request).await,
+    };
+
+    if !interrupted {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_default();
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    Response::from_parts(parts, interrupted_body(&bytes))
+}
+
+/// A body which sends half of `bytes`, and then drops the connection once it has been received.
+fn interrupted_body(bytes: &[u8]) -> Body {
+    let half = bytes[..bytes.len() / 2].to_vec();
+
+    Body::from_stream(futures_util::stream::once(async { Ok(half) }).chain(
+        futures_util::stream::once(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            Err(std::io::Error::other("Mock Ring dropped the connection"))
+        }),
+    ))
+}
+
+/// Check the request carries an access token which was issued by the server.
+fn is_authorized(state: &State, headers: &HeaderMap) -> bool {
+    headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| state.access_tokens.contains(token))
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({ "error": "Unauthorized" })),
+    )
+        .into_response()
+}
+
+/// Issue a brand new set of tokens.
+fn issue_tokens(state: &mut State) -> Response {
+    let id = state.next_id();
+
+    let access_token = format!("mock-access-token-{id}");
+    let refresh_token = format!("mock-refresh-token-{id}");
+
+    state.access_tokens.insert(access_token.clone());
+    state.refresh_tokens.insert(refresh_token.clone());
+
+    Json(json!({
+        "access_token": access_token,
+        "refresh_token": refresh_token,
+        "expires_in": state.token_lifetime,
+        "scope": "client",
+        "token_type": "Bearer",
+    }))
+    .into_response()
+}
+
+async fn oauth_token(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    grant_tokens(&mut lock(&state), &headers, &body)
+}
+
+fn grant_tokens(state: &mut State, headers: &HeaderMap, body: &Value) -> Response {
+    match body["grant_type"].as_str() {
+        Some("password") => {
+            let username = body["username"].as_str().unwrap_or_default();
+            let password = body["password"].as_str().unwrap_or_default();
+
+            if state.users.get(username).map(String::as_str) != Some(password) {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({
+                        "error": "invalid_grant",
+                        "error_description": "invalid user credentials"
+                    })),
+                )
+                    .into_response();
+            }
+
+            if let Some(expected_code) = &state.mfa_code {
+                let code = headers
+                    .get("2fa-code")
+                    .and_then(|value| value.to_str().ok());
+
+                match code {
+                    None => {
+                        return (
+                            StatusCode::PRECONDITION_FAILED,
+                            Json(json!({
+                                "next_time_in_secs": 60,
+                                "phone": "+xxxxxxxxx00",
+                                "tsv_state": "sms"
+                            })),
+                        )
+                            .into_response();
+                    }
+                    Some(code) if code != expected_code => {
+                        return (
+                            StatusCode::BAD_REQUEST,
+                            Json(json!({
+                                "error": "invalid_grant",
+                                "error_description": "Verification Code is invalid or expired"
+                            })),
+                        )
+                            .into_response();
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            issue_tokens(state)
+        }
+        Some("refresh_token") => {
+            let refresh_token = body["refresh_token"].as_str().unwrap_or_default();
+
+            // Much like Ring, refresh tokens can only be used once.
+            if !state.refresh_tokens.remove(refresh_token) {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({
+                        "error": "invalid_grant",
+                        "error_description": "token is invalid or does not exists"
+                    })),
+                )
+                    .into_response();
+            }
+
+            issue_tokens(state)
+        }
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "unsupported_grant_type" })),
+        )
+            .into_response(),
+    }
+}
+
+async fn session(Extract(state): Extract<Shared>, headers: HeaderMap) -> Response {
+    let state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    Json(json!({
+        "profile": {
+            "id": 1,
+            "email": "mock@example.com",
+            "first_name": "Mock",
+            "last_name": "User"
+        }
+    }))
+    .into_response()
+}
+
+async fn devices(Extract(state): Extract<Shared>, headers: HeaderMap) -> Response {
+    let state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    let devices = DEVICE_CATEGORIES
+        .iter()
+        .map(|category| {
+            (
+                (*category).to_string(),
+                Value::Array(state.devices.get(*category).cloned().unwrap_or_default()),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    Json(Value::Object(devices)).into_response()
+}
+
+#[derive(Deserialize)]
+struct HistoryQuery {
+    limit: usize,
+    older_than: Option<u64>,
+}
+
+async fn device_history(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    page_device_history(&lock(&state), &headers, device_id, &query)
+}
+
+fn page_device_history(
+    state: &State,
+    headers: &HeaderMap,
+    device_id: u64,
+    query: &HistoryQuery,
+) -> Response {
+    if !is_authorized(state, headers) {
+        return unauthorized();
+    }
+
+    let history = page_history(
+        state,
+        |item| item["doorbot"]["id"] == device_id,
+        query.older_than,
+        query.limit,
+    );
+
+    Json(history).into_response()
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    limit: usize,
+    kind: Option<String>,
+    older_than_id: Option<u64>,
+    pagination_key: Option<String>,
+}
+
+/// Return a page of the history of the devices in a location, in the shape of location
+/// events.
+///
+/// The pagination key is the ID of the last event in the page.
+async fn location_events(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(location_id): Path<String>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    page_location_events(&lock(&state), &headers, &location_id, query)
+}
+
+fn page_location_events(
+    state: &State,
+    headers: &HeaderMap,
+    location_id: &str,
+    query: EventsQuery,
+) -> Response {
+    if !is_authorized(state, headers) {
+        return unauthorized();
+    }
+
+    let older_than = match query.pagination_key {
+        Some(key) => match key.parse() {
+            Ok(older_than) => Some(older_than),
+            Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+        },
+        None => query.older_than_id,
+    };
+
+    let history = page_history(
+        state,
+        |item| {
+            query
+                .kind
+                .as_ref()
+                .is_none_or(|kind| item["kind"] == **kind)
+                && item["doorbot"]["id"].as_u64().is_some_and(|device_id| {
+                    find_device(state, device_id)
+                        .is_some_and(|device| device["location_id"] == location_id)
+                })
+        },
+        older_than,
+        query.limit,
+    );
+
+    let pagination_key = history.last().map(|item| item["id"].to_string());
+
+    let events = history
+        .into_iter()
+        .map(|item| {
+            json!({
+                "ding_id": item["id"],
+                "ding_id_str": item["id"].to_string(),
+                "kind": item["kind"],
+                "created_at": item["created_at"],
+                "state": if item["answered"] == true { "accepted" } else { "timed_out" },
+                "favorite": item["favorite"],
+                "recording_status": item["recording"]["status"],
+                "doorbot_id": item["doorbot"]["id"],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Json(json!({
+        "events": events,
+        "meta": { "pagination_key": pagination_key }
+    }))
+    .into_response()
+}
+
+/// Return a page of the history items which match, newest first.
+fn page_history(
+    state: &State,
+    matches: impl Fn(&Value) -> bool,
+    older_than: Option<u64>,
+    limit: usize,
+) -> Vec<Value> {
+    let mut history = state
+        .history
+        .iter()
+        .filter(|item| matches(item))
+        .filter(|item| older_than.is_none_or(|older_than| item["id"].as_u64() < Some(older_than)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    history.sort_by_key(|item| std::cmp::Reverse(item["id"].as_u64()));
+    history.truncate(limit);
+
+    history
+}
+
+async fn recording_url(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(ding_id): Path<u64>,
+) -> Response {
+    link_recording(&lock(&state), &headers, ding_id)
+}
+
+fn link_recording(state: &State, headers: &HeaderMap, ding_id: u64) -> Response {
+    if !is_authorized(state, headers) {
+        return unauthorized();
+    }
+
+    if !state.recordings.contains_key(&ding_id) {
+        return (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))).into_response();
+    }
+
+    Json(json!({ "url": format!("http://{}/recordings/{ding_id}", state.address) })).into_response()
+}
+
+/// Download a recording, much like the (presigned) URLs Ring links to, which don't need to be
+/// authorized.
+async fn recording(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(ding_id): Path<u64>,
+) -> Response {
+    let Some((recording, interrupted)) = start_download(&mut lock(&state), ding_id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let start = headers
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok())
+        .filter(|start| *start < recording.len());
+
+    let (status, content_range, bytes) = match start {
+        Some(start) => (
+            StatusCode::PARTIAL_CONTENT,
+            Some(format!(
+                "bytes {start}-{}/{}",
+                recording.len() - 1,
+                recording.len()
+            )),
+            recording[start..].to_vec(),
+        ),
+        None => (StatusCode::OK, None, recording),
+    };
+
+    let body = if interrupted {
+        interrupted_body(&bytes)
+    } else {
+        Body::from(bytes)
+    };
+
+    let mut response = (status, body).into_response();
+
+    if let Some(content_range) = content_range.and_then(|range| HeaderValue::from_str(&range).ok())
+    {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_RANGE, content_range);
+    }
+
+    response
+}
+
+/// Get a recording to download, and whether the download should be interrupted.
+fn start_download(state: &mut State, ding_id: u64) -> Option<(Vec<u8>, bool)> {
+    let recording = state.recordings.get(&ding_id)?.clone();
+
+    let interrupted = state.interrupted_downloads > 0;
+    state.interrupted_downloads = state.interrupted_downloads.saturating_sub(1);
+
+    Some((recording, interrupted))
+}
+
+async fn snapshot(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    let state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    let image = state
+        .snapshots
+        .get(&device_id)
+        .map(|snapshot| snapshot.image.clone());
+
+    drop(state);
+
+    image.map_or_else(
+        || StatusCode::NOT_FOUND.into_response(),
+        |image| ([(header::CONTENT_TYPE, "image/jpeg")], image).into_response(),
+    )
+}
+
+#[derive(Deserialize)]
+struct SnapshotRequest {
+    doorbot_ids: Vec<u64>,
+}
+
+/// Report when the latest snapshot of each camera was captured, and complete any captures
+/// which were in progress (so they're reported the next time the timestamps are checked).
+async fn snapshot_timestamps(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Json(request): Json<SnapshotRequest>,
+) -> Response {
+    let mut state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    let mut timestamps = Vec::new();
+
+    for device_id in request.doorbot_ids {
+        let capturing = state
+            .snapshots
+            .get(&device_id)
+            .is_some_and(|snapshot| snapshot.capturing);
+
+        let captured_at = capturing.then(|| state.snapshot_timestamp());
+
+        let Some(snapshot) = state.snapshots.get_mut(&device_id) else {
+            continue;
+        };
+
+        timestamps.push(json!({ "doorbot_id": device_id, "timestamp": snapshot.timestamp }));
+
+        if let Some(captured_at) = captured_at {
+            if let Some(image) = snapshot.next.take() {
+                snapshot.image = image;
+            }
+
+            snapshot.timestamp = captured_at;
+            snapshot.capturing = false;
+        }
+    }
+
+    drop(state);
+
+    Json(json!({ "timestamps": timestamps })).into_response()
+}
+
+async fn update_snapshots(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Json(request): Json<SnapshotRequest>,
+) -> Response {
+    let mut state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    for device_id in request.doorbot_ids {
+        if let Some(snapshot) = state.snapshots.get_mut(&device_id) {
+            snapshot.capturing = true;
+        }
+    }
+
+    drop(state);
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn light_on(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    update_device(
+        &mut lock(&state),
+        &headers,
+        device_id,
+        "led_status",
+        json!("on"),
+    )
+}
+
+async fn light_off(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    update_device(
+        &mut lock(&state),
+        &headers,
+        device_id,
+        "led_status",
+        json!("off"),
+    )
+}
+
+/// How long the siren sounds for once it is turned on, in seconds.
+const SIREN_DURATION: u64 = 30;
+
+async fn siren_on(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    update_device(
+        &mut lock(&state),
+        &headers,
+        device_id,
+        "siren_status",
+        json!({ "seconds_remaining": SIREN_DURATION }),
+    )
+}
+
+async fn siren_off(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    update_device(
+        &mut lock(&state),
+        &headers,
+        device_id,
+        "siren_status",
+        json!({ "seconds_remaining": 0 }),
+    )
+}
+
+async fn play_sound(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Path(device_id): Path<u64>,
+) -> Response {
+    let state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    let exists = find_device(&state, device_id).is_some();
+
+    drop(state);
+
+    if exists {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
+}
+
+/// Set a field of a device, so the change is returned the next time the devices are listed.
+fn update_device(
+    state: &mut State,
+    headers: &HeaderMap,
+    device_id: u64,
+    field: &str,
+    value: Value,
+) -> Response {
+    if !is_authorized(state, headers) {
+        return unauthorized();
+    }
+
+    let Some(device) = state
+        .devices
+        .values_mut()
+        .flatten()
+        .find(|device| device["id"] == device_id)
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    device[field] = value;
+
+    StatusCode::OK.into_response()
+}
+
+fn find_device(state: &State, device_id: u64) -> Option<&Value> {
+    state
+        .devices
+        .values()
+        .flatten()
+        .find(|device| device["id"] == device_id)
+}
+
+async fn locations(Extract(state): Extract<Shared>, headers: HeaderMap) -> Response {
+    let state = lock(&state);
+
+    if !is_authorized(&state, &headers) {
+        return unauthorized();
+    }
+
+    Json(json!({ "user_locations": state.locations })).into_response()
+}
+
+#[derive(Deserialize)]
+struct TicketQuery {
+    #[serde(rename = "locationID")]
+    location_id: String,
+}
+
+async fn ticket(
+    Extract(state): Extract<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<TicketQuery>,
+) -> Response {
+    issue_ticket(&mut lock(&state), &headers, &query)
+}
+
+fn issue_ticket(state: &mut State, headers: &HeaderMap, query: &TicketQuery) -> Response {
+    if !is_authorized(state, headers) {
+        return unauthorized();
+    }
+
+    let ticket = format!("mock-ticket-{}", state.next_id());
+    state.tickets.insert(ticket.clone());
+
+    Json(json!({
+        "ticket": ticket,
+        "host": state.address.to_string(),
+        "subscriptionTopics": [],
+        "assets": [
+            {
+                "uuid": format!("mock-asset-{}", query.location_id),
+                "doorbotId": 0,
+                "kind": "base_station_v1",
+                "status": "online",
+                "brokerHost": state.address.to_string(),
+                "onBattery": false
+            }
+        ]
+    }))
+    .into_response()
+}
+
+#[derive(Deserialize)]
+struct ConnectQuery {
+    authcode: String,
+}
+
+async fn connect(
+    Extract(state): Extract<Shared>,
+    Query(query): Query<ConnectQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    if !lock(&state).tickets.contains(&query.authcode) {
+        return unauthorized();
+    }
+
+    upgrade.on_upgrade(move |socket| websocket::handle(socket, state))
+}
@@ -0,0 +1,142 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::{Notify, broadcast};
+
+/// The number of pushed messages which can be buffered per WebSocket.
+const OUTBOUND_CAPACITY: usize = 128;
+
+/// A response queued using [`super::MockRing::enqueue_response`].
+#[derive(Debug)]
+pub struct ScriptedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+/// The snapshots of a camera, added using [`super::MockRing::add_snapshot`].
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    pub image: Vec<u8>,
+
+    /// When the image was captured, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// The image captured when a fresh snapshot is next requested.
+    pub next: Option<Vec<u8>>,
+
+    /// Whether a fresh snapshot has been requested, and is yet to be captured.
+    pub capturing: bool,
+}
+
+/// The mutable state behind a [`super::MockRing`] server.
+#[derive(Debug)]
+pub struct State {
+    pub address: SocketAddr,
+    pub users: HashMap<String, String>,
+    pub mfa_code: Option<String>,
+    pub refresh_tokens: HashSet<String>,
+    pub access_tokens: HashSet<String>,
+    pub tickets: HashSet<String>,
+    pub token_lifetime: i64,
+    pub locations: Vec<Value>,
+    pub devices: HashMap<String, Vec<Value>>,
+    pub alarm_devices: Vec<Value>,
+
+    /// Whether to drop the WebSocket after acknowledging the next change to the alarm mode,
+    /// before confirming it.
+    pub disconnect_before_confirming: bool,
+
+    /// Whether the WebSocket should be dropped once the replies to a message have been sent.
+    pub hang_up: bool,
+
+    pub history: Vec<Value>,
+    pub recordings: HashMap<u64, Vec<u8>>,
+    pub interrupted_downloads: usize,
+    pub interrupted_responses: HashMap<String, usize>,
+    pub snapshots: HashMap<u64, Snapshot>,
+    pub scripted: HashMap<String, VecDeque<ScriptedResponse>>,
+    pub requests: HashMap<String, usize>,
+    pub outbound: broadcast::Sender<Value>,
+    pub disconnects: broadcast::Sender<()>,
+    pub inbound: Vec<Value>,
+    pub inbound_notify: Arc<Notify>,
+    issued: usize,
+}
+
+impl State {
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            users: HashMap::new(),
+            mfa_code: None,
+            refresh_tokens: HashSet::new(),
+            access_tokens: HashSet::new(),
+            tickets: HashSet::new(),
+            token_lifetime: 3600,
+            locations: Vec::new(),
+            devices: HashMap::new(),
+            alarm_devices: Vec::new(),
+            disconnect_before_confirming: false,
+            hang_up: false,
+            history: Vec::new(),
+            recordings: HashMap::new(),
+            interrupted_downloads: 0,
+            interrupted_responses: HashMap::new(),
+            snapshots: HashMap::new(),
+            scripted: HashMap::new(),
+            requests: HashMap::new(),
+            outbound: broadcast::channel(OUTBOUND_CAPACITY).0,
+            disconnects: broadcast::channel(1).0,
+            inbound: Vec::new(),
+            inbound_notify: Arc::new(Notify::new()),
+            issued: 0,
+        }
+    }
+
+    /// Generate a unique, incrementing suffix for issued tokens and tickets.
+    pub const fn next_id(&mut self) -> usize {
+        self.issued += 1;
+        self.issued
+    }
+
+    /// Generate a timestamp for a captured snapshot, which is later than any before it.
+    pub const fn snapshot_timestamp(&mut self) -> u64 {
+        1_700_000_000_000 + self.next_id() as u64 * 1000
+    }
+
+    /// Replace the latest snapshot of a camera, as if it had just been captured.
+    pub fn capture_snapshot(&mut self, device_id: u64, image: Vec<u8>) {
+        let timestamp = self.snapshot_timestamp();
+        let snapshot = self.snapshots.entry(device_id).or_default();
+
+        snapshot.image = image;
+        snapshot.timestamp = timestamp;
+    }
+
+    /// Record a message received over a WebSocket, and wake anyone waiting for it.
+    pub fn record_inbound(&mut self, message: Value) {
+        self.inbound.push(message);
+        self.inbound_notify.notify_waiters();
+    }
+
+    /// Record a request to a path, and return the scripted response for it (if any).
+    pub fn record_request(&mut self, path: &str) -> Option<ScriptedResponse> {
+        *self.requests.entry(path.to_string()).or_default() += 1;
+
+        self.scripted.get_mut(path).and_then(VecDeque::pop_front)
+    }
+
+    /// Whether the response to a request to a path should be cut short, counting it against
+    /// the responses left to interrupt.
+    pub fn interrupt_response(&mut self, path: &str) -> bool {
+        self.interrupted_responses
+            .get_mut(path)
+            .filter(|count| **count > 0)
+            .is_some_and(|count| {
+                *count -= 1;
+                true
+            })
+    }
+}
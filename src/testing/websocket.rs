@@ -0,0 +1,188 @@
+use crate::testing::state::State;
+use axum::extract::ws::{Message, WebSocket};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+
+/// Wrap the body of a message in the envelope used by Ring.
+fn envelope(message: &Value) -> Message {
+    Message::Text(
+        json!({ "channel": "message", "msg": message })
+            .to_string()
+            .into(),
+    )
+}
+
+/// The replies to a message received from the client, if Ring would send any.
+fn respond(state: &mut State, message: &Value) -> Vec<Value> {
+    match message["msg"].as_str() {
+        Some("DeviceInfoDocGetList") => vec![json!({
+            "msg": "DeviceInfoDocGetList",
+            "datatype": "DeviceInfoDocType",
+            "src": message["dst"],
+            "seq": message["seq"],
+            "body": state.alarm_devices,
+        })],
+        Some("DeviceInfoSet") => message["body"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|document| switch_mode(state, message, document))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Handle a `security-panel.switch-mode` command, refusing to arm while unbypassed sensors are
+/// faulted.
+fn switch_mode(state: &mut State, message: &Value, document: &Value) -> Vec<Value> {
+    let command = &document["command"]["v1"][0];
+
+    if command["commandType"] != "security-panel.switch-mode" {
+        return Vec::new();
+    }
+
+    let mode = &command["data"]["mode"];
+    let bypassed = command["data"]["bypass"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+
+    let faulted = state.alarm_devices.iter().any(|device| {
+        device["device"]["v1"]["faulted"] == true
+            && !bypassed.contains(&device["general"]["v2"]["zid"])
+    });
+
+    if faulted && mode != "none" {
+        return vec![json!({
+            "msg": "DeviceInfoSet",
+            "src": message["dst"],
+            "seq": message["seq"],
+            "status": 1,
+            "error": "Sensors are faulted"
+        })];
+    }
+
+    let Some(panel) = state
+        .alarm_devices
+        .iter_mut()
+        .find(|device| device["general"]["v2"]["zid"] == document["zid"])
+    else {
+        return Vec::new();
+    };
+
+    panel["device"]["v1"]["mode"] = mode.clone();
+
+    let acknowledgement = json!({
+        "msg": "DeviceInfoSet",
+        "src": message["dst"],
+        "seq": message["seq"],
+        "status": 0
+    });
+
+    let confirmation = json!({
+        "msg": "DataUpdate",
+        "datatype": "DeviceInfoDocType",
+        "src": message["dst"],
+        "body": [panel]
+    });
+
+    if std::mem::take(&mut state.disconnect_before_confirming) {
+        state.hang_up = true;
+
+        return vec![acknowledgement];
+    }
+
+    vec![acknowledgement, confirmation]
+}
+
+/// Drive a single WebSocket connection until either side closes it.
+pub async fn handle(mut socket: WebSocket, state: Arc<Mutex<State>>) {
+    let (mut outbound, mut disconnects, asset) = {
+        let state = state
+            .lock()
+            .expect("Mock Ring state should not be poisoned");
+
+        (
+            state.outbound.subscribe(),
+            state.disconnects.subscribe(),
+            state.address.to_string(),
+        )
+    };
+
+    let greeting = [
+        json!({
+            "msg": "SessionInfo",
+            "datatype": "SessionInfoType",
+            "body": [{ "assetUuid": asset, "status": "online" }]
+        }),
+        json!({
+            "msg": "SubscriptionTopicsInfo",
+            "datatype": "SubscriptionTopicType",
+            "body": [{ "topic": "DataUpdate", "status": "subscribed" }]
+        }),
+    ];
+
+    for message in greeting {
+        if socket.send(envelope(&message)).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        let Ok(mut message) = serde_json::from_str::<Value>(&text) else {
+                            log::warn!("Mock Ring received a message which is not JSON: {text}");
+                            continue;
+                        };
+
+                        let (replies, hang_up) = {
+                            let mut state = state
+                                .lock()
+                                .expect("Mock Ring state should not be poisoned");
+
+                            let message = message["msg"].take();
+                            let replies = respond(&mut state, &message);
+
+                            state.record_inbound(message);
+
+                            (replies, std::mem::take(&mut state.hang_up))
+                        };
+
+                        for reply in replies {
+                            if socket.send(envelope(&reply)).await.is_err() {
+                                return;
+                            }
+                        }
+
+                        if hang_up {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                    Some(Ok(_)) => {}
+                }
+            }
+            _ = disconnects.recv() => {
+                // Drop the connection abruptly, without a close frame, as a broker would.
+                return;
+            }
+            message = outbound.recv() => {
+                match message {
+                    Ok(message) => {
+                        if socket.send(envelope(&message)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Mock Ring WebSocket lagged, {skipped} messages were dropped");
+                    }
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        }
+    }
+}
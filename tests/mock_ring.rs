@@ -0,0 +1,1999 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use ring_client::authentication::{Credentials, FileTokenStore, TokenStore};
+use ring_client::capability::{BatteryPowered, Chime, ChimeSound, Light, Siren};
+use ring_client::device::{Device, DeviceCategory};
+use ring_client::history::{HistoryKind, HistoryQuery, RecordingStatus};
+use ring_client::location::{
+    AlarmDevice, AlarmMode, Event, EventKind, Filter, ListenerEvent, Location, LocationEvent,
+    Message, ReconnectPolicy, Zid,
+};
+use ring_client::testing::{MockRing, fixtures};
+use ring_client::{ApiError, AuthenticationError, Client, RetryPolicy};
+use serde_json::json;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::{sync::Mutex, time::timeout};
+
+/// Start a mock with a single location (`mock-location-id`), which most tests need.
+async fn start_mock_at_home() -> MockRing {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-id", "Home"));
+
+    mock
+}
+
+/// Log a client in to the mock, using a refresh token which the mock accepts.
+async fn log_in(mock: &MockRing, client: &Client) {
+    mock.add_refresh_token("refresh-token");
+
+    client
+        .login(Credentials::RefreshToken("refresh-token".to_string()))
+        .await
+        .expect("Refresh token should be valid");
+}
+
+/// Create a client for the mock, and log it in.
+async fn logged_in_client(mock: &MockRing) -> Client {
+    let client = mock.client();
+
+    log_in(mock, &client).await;
+
+    client
+}
+
+/// Get the only location the client can access.
+async fn only_location(client: &Client) -> Location {
+    client
+        .get_locations()
+        .await
+        .expect("Should be able to get locations")
+        .pop()
+        .expect("Expected at least one location")
+}
+
+#[tokio::test]
+async fn test_logging_in_with_mfa_challenge() {
+    let mock = MockRing::start().await;
+
+    mock.add_user("username", "password");
+    mock.require_mfa("123456");
+
+    let client = mock.client();
+
+    let attempt = client
+        .login(Credentials::User {
+            username: "username".to_string(),
+            password: "password".to_string(),
+        })
+        .await;
+
+    assert!(
+        matches!(attempt, Err(AuthenticationError::MfaCodeRequired)),
+        "Expected an MFA challenge to be issued"
+    );
+
+    client
+        .respond_to_challenge("123456")
+        .await
+        .expect("Responding with the correct code should not fail");
+
+    assert!(client.get_refresh_token().await.is_some());
+}
+
+#[tokio::test]
+async fn test_logging_in_with_invalid_credentials() {
+    let mock = MockRing::start().await;
+
+    mock.add_user("username", "password");
+
+    let attempt = mock
+        .client()
+        .login(Credentials::User {
+            username: "username".to_string(),
+            password: "incorrect".to_string(),
+        })
+        .await;
+
+    assert!(matches!(
+        attempt,
+        Err(AuthenticationError::InvalidCredentials)
+    ));
+}
+
+#[tokio::test]
+async fn test_surviving_restarts_using_token_store() {
+    let mock = MockRing::start().await;
+
+    mock.add_refresh_token("refresh-token");
+
+    // Tokens expire immediately, so every request will need to refresh them first.
+    mock.set_token_lifetime(0);
+
+    let path = std::env::temp_dir().join(format!(
+        "ring-client-mock-tokens-{}.json",
+        std::process::id()
+    ));
+    let store = FileTokenStore::new(&path);
+
+    let client = mock.client_builder().token_store(store.clone()).build();
+
+    log_in(&mock, &client).await;
+
+    let refresh_token = client
+        .get_refresh_token()
+        .await
+        .expect("Client should have a refresh token");
+
+    assert_ne!(
+        refresh_token, "refresh-token",
+        "Tokens should have been rotated"
+    );
+    assert_eq!(
+        store
+            .load()
+            .expect("Loading tokens should not fail")
+            .expect("Tokens should have been saved")
+            .refresh_token(),
+        refresh_token,
+        "The latest refresh token should have been saved"
+    );
+
+    // A brand new client (i.e. after a restart) should be able to log in using the tokens
+    // saved by the previous client, even though the original refresh token is now invalid.
+    let restarted_client = mock.client_builder().token_store(store.clone()).build();
+
+    restarted_client
+        .login(Credentials::Stored)
+        .await
+        .expect("Stored tokens should be valid");
+
+    std::fs::remove_file(&path).expect("Tokens file should be removable");
+}
+
+#[tokio::test]
+async fn test_being_notified_of_rotated_refresh_tokens() {
+    let mock = MockRing::start().await;
+
+    // Tokens expire immediately, so every request will need to refresh them first.
+    mock.set_token_lifetime(0);
+
+    let client = mock.client();
+
+    let notified_tokens = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    {
+        let notified_tokens = Arc::clone(&notified_tokens);
+
+        client.on_token_refresh(move |refresh_token| {
+            notified_tokens
+                .lock()
+                .expect("Lock should not be poisoned")
+                .push(refresh_token.to_string());
+        });
+    }
+
+    let refresh_tokens = client.watch_refresh_token();
+
+    assert_eq!(*refresh_tokens.borrow(), None);
+
+    log_in(&mock, &client).await;
+
+    let notified_tokens = notified_tokens
+        .lock()
+        .expect("Lock should not be poisoned")
+        .clone();
+
+    // Once on login, and once more when the (immediately expired) tokens were refreshed in
+    // order to set the session.
+    assert_eq!(notified_tokens.len(), 2);
+    assert_eq!(
+        notified_tokens.last(),
+        client.get_refresh_token().await.as_ref()
+    );
+    assert_eq!(*refresh_tokens.borrow(), client.get_refresh_token().await);
+    assert_eq!(
+        mock.refresh_tokens(),
+        vec![notified_tokens[1].clone()],
+        "Only the latest refresh token should be valid"
+    );
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_share_a_single_refresh() {
+    let mock = MockRing::start().await;
+
+    // Tokens which expire within the (default) refresh skew of one minute should be refreshed.
+    mock.set_token_lifetime(30);
+
+    let client = logged_in_client(&mock).await;
+
+    mock.set_token_lifetime(3600);
+
+    let grants = mock.request_count("/oauth/token");
+
+    let (first, second, third) = tokio::join!(
+        client.get_devices(),
+        client.get_devices(),
+        client.get_locations()
+    );
+
+    assert!(first.is_ok() && second.is_ok() && third.is_ok());
+    assert_eq!(
+        mock.request_count("/oauth/token"),
+        grants + 1,
+        "Only one refresh should have been made"
+    );
+}
+
+#[tokio::test]
+async fn test_refreshing_tokens_in_the_background() {
+    let mock = MockRing::start().await;
+
+    // Tokens will need refreshing a second after being issued.
+    mock.set_token_lifetime(61);
+
+    let client = mock
+        .client_builder()
+        .refresh_skew(Duration::from_secs(60))
+        .background_refresh(true)
+        .build();
+
+    log_in(&mock, &client).await;
+
+    let mut refresh_tokens = client.watch_refresh_token();
+    let initial_refresh_token = refresh_tokens.borrow_and_update().clone();
+
+    timeout(Duration::from_secs(5), refresh_tokens.changed())
+        .await
+        .expect("Tokens should have been refreshed in the background")
+        .expect("Client should still be alive");
+
+    assert_ne!(*refresh_tokens.borrow(), initial_refresh_token);
+}
+
+#[tokio::test]
+async fn test_capping_the_refresh_skew() {
+    let mock = MockRing::start().await;
+
+    let client = mock.client_builder().refresh_skew(Duration::MAX).build();
+
+    log_in(&mock, &client).await;
+
+    let grants = mock.request_count("/oauth/token");
+
+    client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(
+        mock.request_count("/oauth/token"),
+        grants + 1,
+        "Tokens within the (capped) skew should be refreshed"
+    );
+}
+
+#[tokio::test]
+async fn test_recovering_from_revoked_access_tokens() {
+    let mock = MockRing::start().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let refresh_token = client.get_refresh_token().await;
+
+    mock.revoke_access_tokens();
+
+    client
+        .get_devices()
+        .await
+        .expect("Rejected tokens should have been refreshed");
+
+    assert_eq!(mock.request_count("/clients_api/ring_devices"), 2);
+    assert_ne!(
+        client.get_refresh_token().await,
+        refresh_token,
+        "Tokens should have been refreshed"
+    );
+}
+
+#[tokio::test]
+async fn test_requests_fail_when_refreshed_tokens_are_rejected() {
+    let mock = MockRing::start().await;
+
+    let client = logged_in_client(&mock).await;
+
+    mock.enqueue_response("/clients_api/ring_devices", 401, json!({}));
+    mock.enqueue_response("/clients_api/ring_devices", 401, json!({}));
+
+    let attempt = client.get_devices().await;
+
+    assert!(matches!(attempt, Err(ApiError::Unauthorized)));
+    assert_eq!(
+        mock.request_count("/clients_api/ring_devices"),
+        2,
+        "The request should only have been retried once"
+    );
+}
+
+#[tokio::test]
+async fn test_unsuccessful_statuses_are_reported() {
+    let mock = MockRing::start().await;
+
+    let client = logged_in_client(&mock).await;
+
+    mock.enqueue_response_with_headers(
+        "/clients_api/ring_devices",
+        429,
+        &[("Retry-After", "30")],
+        json!({ "error": "Too many requests" }),
+    );
+    mock.enqueue_response(
+        "/devices/v1/locations",
+        503,
+        json!({ "error": "Unavailable" }),
+    );
+
+    let devices = client.get_devices().await;
+
+    assert!(
+        matches!(
+            devices,
+            Err(ApiError::RateLimited { retry_after: Some(retry_after), ref body })
+                if retry_after == Duration::from_secs(30) && body.contains("Too many requests")
+        ),
+        "Expected the request to be rate limited, got {devices:?}"
+    );
+
+    let locations = client.get_locations().await;
+
+    assert!(
+        matches!(
+            locations,
+            Err(ApiError::ServerError { status, ref body })
+                if status.as_u16() == 503 && body.contains("Unavailable")
+        ),
+        "Expected a server error, got {locations:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_retrying_transient_failures() {
+    let mock = MockRing::start().await;
+
+    let client = mock
+        .client_builder()
+        .retry_policy(
+            RetryPolicy::default()
+                .max_attempts(3)
+                .base_delay(Duration::from_millis(10)),
+        )
+        .build();
+
+    log_in(&mock, &client).await;
+
+    mock.enqueue_response("/clients_api/ring_devices", 503, json!({}));
+    mock.enqueue_response("/clients_api/ring_devices", 502, json!({}));
+
+    client
+        .get_devices()
+        .await
+        .expect("Transient failures should have been retried");
+
+    assert_eq!(mock.request_count("/clients_api/ring_devices"), 3);
+}
+
+#[tokio::test]
+async fn test_not_retrying_non_idempotent_requests() {
+    let mock = MockRing::start().await;
+
+    mock.add_refresh_token("refresh-token");
+
+    let client = mock
+        .client_builder()
+        .retry_policy(
+            RetryPolicy::default()
+                .max_attempts(3)
+                .base_delay(Duration::from_millis(10)),
+        )
+        .build();
+
+    mock.enqueue_response("/clients_api/session", 503, json!({}));
+
+    let attempt = client
+        .login(Credentials::RefreshToken("refresh-token".to_string()))
+        .await;
+
+    assert!(matches!(attempt, Err(AuthenticationError::SessionFailed)));
+    assert_eq!(mock.request_count("/clients_api/session"), 1);
+}
+
+#[tokio::test]
+async fn test_retrying_responses_cut_short() {
+    let mock = start_mock_at_home().await;
+
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-id", "Garden"),
+    );
+    mock.add_snapshot(1, b"a snapshot".to_vec());
+
+    let client = mock
+        .client_builder()
+        .retry_policy(
+            RetryPolicy::default()
+                .max_attempts(3)
+                .base_delay(Duration::from_millis(10)),
+        )
+        .build();
+
+    log_in(&mock, &client).await;
+
+    mock.interrupt_responses("/clients_api/ring_devices", 1);
+    mock.interrupt_responses("/clients_api/snapshots/image/1", 2);
+
+    let devices = client
+        .get_devices()
+        .await
+        .expect("A response cut short should have been retried");
+
+    let snapshot = devices[0]
+        .snapshot()
+        .await
+        .expect("A snapshot cut short should have been retried");
+
+    assert_eq!(&snapshot[..], b"a snapshot");
+    assert_eq!(mock.request_count("/clients_api/ring_devices"), 2);
+    assert_eq!(mock.request_count("/clients_api/snapshots/image/1"), 3);
+}
+
+#[tokio::test]
+async fn test_listing_devices_and_locations() {
+    let mock = start_mock_at_home().await;
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-id", "Garden"),
+    );
+    mock.add_device(
+        "doorbots",
+        fixtures::device(
+            "doorbell_graham_cracker",
+            2,
+            "mock-location-id",
+            "Front Door",
+        ),
+    );
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(devices.len(), 2);
+
+    let locations = client
+        .get_locations()
+        .await
+        .expect("Expected to get locations");
+
+    assert_eq!(locations.len(), 1);
+    assert_eq!(locations[0].data.name, "Home");
+}
+
+#[tokio::test]
+async fn test_reading_common_device_fields() {
+    let mock = MockRing::start().await;
+
+    let mut doorbell = fixtures::device("doorbell_v4", 1, "mock-location-id", "Front Door");
+
+    doorbell["battery_life"] = json!("87");
+    doorbell["firmware_version"] = json!("1.2.3");
+
+    mock.add_location(fixtures::location("mock-location-id", "Home"));
+    mock.add_device("authorized_doorbots", doorbell);
+    mock.add_device(
+        "chimes",
+        fixtures::device("chime_pro_v2", 2, "mock-location-id", "Hallway"),
+    );
+    mock.add_device(
+        "other",
+        json!({
+            "kind": "mystery_device",
+            "id": 3,
+            "location_id": "mock-location-id",
+            "description": "Mystery",
+            "alerts": { "connection": "offline" }
+        }),
+    );
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(
+        devices.iter().map(Device::kind).collect::<Vec<_>>(),
+        ["doorbell_v4", "chime_pro_v2", "mystery_device"]
+    );
+    assert_eq!(
+        devices.iter().map(Device::category).collect::<Vec<_>>(),
+        [
+            DeviceCategory::AuthorizedDoorbots,
+            DeviceCategory::Chimes,
+            DeviceCategory::Other
+        ]
+    );
+    assert_eq!(
+        devices.iter().map(Device::id).collect::<Vec<_>>(),
+        [Some(1), Some(2), Some(3)]
+    );
+    assert!(
+        devices
+            .iter()
+            .all(|device| device.location_id() == Some("mock-location-id"))
+    );
+    assert_eq!(
+        devices.iter().map(Device::description).collect::<Vec<_>>(),
+        [Some("Front Door"), Some("Hallway"), Some("Mystery")]
+    );
+    assert_eq!(
+        devices
+            .iter()
+            .map(Device::battery_level)
+            .collect::<Vec<_>>(),
+        [Some(87), None, None]
+    );
+    assert_eq!(devices[0].firmware_version(), Some("1.2.3"));
+    assert_eq!(
+        devices.iter().map(Device::is_online).collect::<Vec<_>>(),
+        [Some(true), Some(true), Some(false)]
+    );
+}
+
+#[tokio::test]
+async fn test_using_devices_through_their_capabilities() {
+    let mock = MockRing::start().await;
+
+    let mut doorbell = fixtures::camera("doorbell_v4", 1, "mock-location-id", "Front Door");
+    let mut chime = fixtures::device("chime_pro_v2", 2, "mock-location-id", "Hallway");
+    let mut floodlight = fixtures::camera("cocoa_floodlight", 3, "mock-location-id", "Drive");
+    let mut spotlight = fixtures::camera("hp_cam_v2", 4, "mock-location-id", "Garden");
+    let mut wired = fixtures::camera("doorbell_v5", 5, "mock-location-id", "Back Door");
+    let mut beam = fixtures::device("beams_ct200_transformer", 6, "mock-location-id", "Path");
+
+    doorbell["battery_life"] = json!(60);
+    chime["settings"]["ding_audio_id"] = json!("ding");
+    chime["settings"]["volume"] = json!(7);
+    floodlight["settings"]["floodlight_settings"] = json!({ "duration": 30 });
+    floodlight["settings"]["siren_settings"] = json!({ "enabled": true });
+    floodlight["siren_status"] = json!({ "seconds_remaining": 0 });
+    spotlight["settings"]["floodlight_settings"] = json!({ "duration": 30 });
+    spotlight["led_status"] = json!("off");
+    wired["battery_life"] = json!(100);
+    wired["settings"]["power_mode"] = json!("wired");
+    beam["led_status"] = json!("off");
+
+    mock.add_location(fixtures::location("mock-location-id", "Home"));
+    mock.add_device("doorbots", doorbell);
+    mock.add_device("doorbots", wired);
+    mock.add_device("chimes", chime);
+    mock.add_device("stickup_cams", floodlight);
+    mock.add_device("stickup_cams", spotlight);
+    mock.add_device("beams", beam);
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(
+        devices
+            .iter()
+            .map(|device| device.as_camera().is_some())
+            .collect::<Vec<_>>(),
+        [true, true, false, true, true, false]
+    );
+    assert_eq!(
+        devices
+            .iter()
+            .map(|device| device
+                .as_battery_powered()
+                .map(|battery| battery.battery_level()))
+            .collect::<Vec<_>>(),
+        [Some(60), None, None, None, None, None]
+    );
+    assert_eq!(
+        devices
+            .iter()
+            .map(|device| device.as_chime().is_some())
+            .collect::<Vec<_>>(),
+        [false, false, true, false, false, false]
+    );
+    assert_eq!(
+        devices
+            .iter()
+            .map(|device| device.as_siren().is_some())
+            .collect::<Vec<_>>(),
+        [false, false, false, true, false, false]
+    );
+
+    let chime = devices[2].as_chime().expect("The chime should be a chime");
+
+    assert_eq!(chime.volume(), Some(7));
+
+    chime
+        .play_sound(ChimeSound::Ding)
+        .await
+        .expect("Expected to play a sound on the chime");
+
+    assert_eq!(mock.request_count("/clients_api/chimes/2/play_sound"), 1);
+
+    let lights = devices
+        .iter()
+        .filter_map(Device::as_light)
+        .collect::<Vec<_>>();
+
+    // The Beam reports the status of its light, but is controlled through the Ring Bridge.
+    assert_eq!(lights.len(), 2);
+
+    for light in &lights {
+        light
+            .set_light(true)
+            .await
+            .expect("Expected to turn on the light");
+    }
+
+    devices[3]
+        .as_siren()
+        .expect("The Floodlight Cam should have a siren")
+        .set_siren(true)
+        .await
+        .expect("Expected to sound the siren");
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(
+        devices
+            .iter()
+            .filter_map(Device::as_light)
+            .map(|light| light.is_light_on())
+            .collect::<Vec<_>>(),
+        [Some(true), Some(true)]
+    );
+    assert_eq!(
+        devices[3].as_siren().and_then(|siren| siren.is_siren_on()),
+        Some(true)
+    );
+}
+
+#[tokio::test]
+async fn test_skipping_devices_and_locations_which_cannot_be_decoded() {
+    let mock = MockRing::start().await;
+
+    let mut odd_location = fixtures::location("mock-location-2", "Office");
+    let mut odd_device = fixtures::camera("cocoa_camera", 2, "mock-location-id", "Garden");
+
+    odd_location["address"]
+        .as_object_mut()
+        .expect("The address should be an object")
+        .remove("cross_street");
+    odd_device
+        .as_object_mut()
+        .expect("The device should be an object")
+        .remove("description");
+
+    mock.add_location(fixtures::location("mock-location-id", "Home"));
+    mock.add_location(odd_location.clone());
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-id", "Driveway"),
+    );
+    mock.add_device("stickup_cams", odd_device.clone());
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    assert_eq!(devices.len(), 1);
+
+    let listing = client
+        .get_device_listing()
+        .await
+        .expect("Expected to get devices");
+
+    assert_eq!(listing.items.len(), 1);
+    assert_eq!(listing.errors.len(), 1);
+    assert_eq!(listing.errors[0].raw, odd_device);
+
+    let listing = client
+        .get_location_listing()
+        .await
+        .expect("Expected to get locations");
+
+    assert_eq!(listing.items.len(), 1);
+    assert_eq!(listing.items[0].id(), "mock-location-id");
+    assert_eq!(listing.errors.len(), 1);
+    assert_eq!(listing.errors[0].raw, odd_location);
+}
+
+#[tokio::test]
+async fn test_paging_through_device_history() {
+    let mock = start_mock_at_home().await;
+    mock.add_device(
+        "doorbots",
+        fixtures::camera(
+            "doorbell_graham_cracker",
+            1,
+            "mock-location-id",
+            "Front Door",
+        ),
+    );
+
+    for id in 1..=120 {
+        let kind = if id % 2 == 0 { "ding" } else { "motion" };
+        let mut item = fixtures::history_item(id, kind, 1);
+
+        // The last item on the first page can't be decoded, but the history carries on past it.
+        if id == 71 {
+            item["created_at"] = json!("yesterday");
+        }
+
+        mock.add_history_item(item);
+        mock.add_history_item(fixtures::history_item(1000 + id, kind, 2));
+    }
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+    let device = devices.first().expect("Expected at least one device");
+
+    let history = device
+        .history(HistoryQuery::default())
+        .expect("The device should have a history")
+        .map(|item| item.expect("Reading the history should not fail").id)
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        history,
+        (1..=120).rev().filter(|id| *id != 71).collect::<Vec<_>>()
+    );
+    assert!(mock.request_count("/clients_api/doorbots/1/history") > 1);
+
+    let dings = device
+        .history(HistoryQuery {
+            limit: Some(3),
+            older_than: Some(100),
+            kind: Some(HistoryKind::Ding),
+        })
+        .expect("The device should have a history")
+        .map(|item| item.expect("Reading the history should not fail"))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        dings.iter().map(|item| item.id).collect::<Vec<_>>(),
+        vec![98, 96, 94]
+    );
+    assert_eq!(dings[0].device_id, Some(1));
+    assert_eq!(dings[0].recording_status, Some(RecordingStatus::Ready));
+}
+
+#[tokio::test]
+async fn test_reading_location_history() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-1", "Garden"),
+    );
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 2, "mock-location-2", "Car Park"),
+    );
+    mock.add_device("other", json!({ "kind": "mystery_device", "id": 3 }));
+    mock.add_device(
+        "chimes",
+        fixtures::device("chime_pro_v2", 4, "mock-location-1", "Hallway"),
+    );
+
+    mock.add_history_item(fixtures::history_item(1, "motion", 1));
+    mock.add_history_item(fixtures::history_item(2, "motion", 2));
+    mock.add_history_item(fixtures::history_item(3, "on_demand", 1));
+
+    let client = logged_in_client(&mock).await;
+
+    let location = client
+        .get_locations()
+        .await
+        .expect("Expected to get locations")
+        .into_iter()
+        .find(|location| location.id() == "mock-location-1")
+        .expect("Expected the location to be found");
+
+    let history = location
+        .history(HistoryQuery::default())
+        .map(|item| item.expect("Reading the history should not fail"))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        history.iter().map(|item| item.id).collect::<Vec<_>>(),
+        vec![3, 1]
+    );
+    assert_eq!(history[0].kind, HistoryKind::OnDemand);
+    assert_eq!(history[0].device_id, Some(1));
+    assert_eq!(
+        mock.request_count("/clients_api/locations/mock-location-1/events"),
+        2
+    );
+
+    let motion = location
+        .history(HistoryQuery {
+            kind: Some(HistoryKind::Motion),
+            ..HistoryQuery::default()
+        })
+        .map(|item| item.expect("Reading the history should not fail"))
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(
+        motion.iter().map(|item| item.id).collect::<Vec<_>>(),
+        vec![1]
+    );
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    for kind in ["mystery_device", "chime_pro_v2"] {
+        let device = devices
+            .iter()
+            .find(|device| device.kind() == kind)
+            .expect("Expected the device to be found");
+
+        assert!(matches!(
+            device.history(HistoryQuery::default()).err(),
+            Some(ApiError::UnsupportedDevice(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_resuming_interrupted_recording_downloads() {
+    let mock = start_mock_at_home().await;
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-id", "Garden"),
+    );
+    mock.add_device(
+        "chimes",
+        fixtures::device("chime_pro_v2", 2, "mock-location-id", "Hallway"),
+    );
+
+    let recording = (0..100_000)
+        .map(|byte| (byte % 251) as u8)
+        .collect::<Vec<_>>();
+
+    mock.add_history_item(fixtures::history_item(7, "motion", 1));
+    mock.add_recording(7, recording.clone());
+    mock.interrupt_recording_downloads(1);
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+    let device = devices
+        .iter()
+        .find(|device| device.kind() == "cocoa_camera")
+        .expect("Expected the camera to be found");
+    let chime = devices
+        .iter()
+        .find(|device| device.kind() == "chime_pro_v2")
+        .expect("Expected the chime to be found");
+
+    let url = device
+        .recording_url(7)
+        .await
+        .expect("The recording should have a URL");
+
+    assert_eq!(url, format!("http://{}/recordings/7", mock.address()));
+
+    let mut downloaded = Vec::new();
+
+    let written = device
+        .download_recording(7, &mut downloaded)
+        .await
+        .expect("The download should be resumed after being interrupted");
+
+    assert_eq!(written, recording.len() as u64);
+    assert_eq!(downloaded, recording);
+    assert_eq!(mock.request_count("/recordings/7"), 2);
+
+    assert!(matches!(
+        device.download_recording(8, &mut Vec::new()).await,
+        Err(ApiError::NotFound { .. })
+    ));
+
+    // Only cameras have recordings.
+    assert!(matches!(
+        chime.download_recording(7, &mut Vec::new()).await,
+        Err(ApiError::UnsupportedDevice(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_getting_camera_snapshots() {
+    let mock = start_mock_at_home().await;
+    mock.add_device(
+        "stickup_cams",
+        fixtures::camera("cocoa_camera", 1, "mock-location-id", "Garden"),
+    );
+    mock.add_device(
+        "base_stations",
+        fixtures::device("base_station_v1", 2, "mock-location-id", "Alarm"),
+    );
+
+    mock.add_snapshot(1, b"old snapshot".to_vec());
+    mock.queue_snapshot(1, b"fresh snapshot".to_vec());
+
+    let client = logged_in_client(&mock).await;
+
+    let devices = client.get_devices().await.expect("Expected to get devices");
+
+    let snapshot = devices[0]
+        .snapshot()
+        .await
+        .expect("The camera should have a snapshot");
+
+    assert_eq!(&snapshot[..], b"old snapshot");
+
+    let snapshot = devices[0]
+        .request_fresh_snapshot()
+        .await
+        .expect("A fresh snapshot should be captured");
+
+    assert_eq!(&snapshot[..], b"fresh snapshot");
+    assert!(mock.request_count("/clients_api/snapshots/timestamps") > 2);
+
+    assert!(matches!(
+        devices[1].snapshot().await,
+        Err(ApiError::UnsupportedDevice(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_listening_and_sending_events_in_location() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    listener
+        .send(Event::new(Message::DataUpdate(json!({ "body": [] }))))
+        .await
+        .expect("Sending an event should not fail");
+
+    let received_events = Arc::new(Mutex::new(Vec::new()));
+
+    {
+        let received_events = Arc::clone(&received_events);
+
+        let outcome = timeout(
+            Duration::from_secs(5),
+            listener.listen::<_, _, ()>(|event, _, _| {
+                let received_events = Arc::clone(&received_events);
+
+                async move {
+                    let mut received_events = received_events.lock().await;
+
+                    let ListenerEvent::Event(event) = event else {
+                        panic!("Expected only events from Ring, got {event:?}");
+                    };
+
+                    received_events.push(event);
+
+                    Ok(received_events.len() < 2)
+                }
+            }),
+        )
+        .await;
+
+        assert!(
+            matches!(outcome, Ok(Ok(()))),
+            "Outcome of event listening should be Ok"
+        );
+    }
+
+    let events = received_events.lock().await;
+
+    assert!(matches!(events[0].message, Message::SessionInfo(_)));
+    assert!(matches!(
+        events[1].message,
+        Message::SubscriptionTopicsInfo(_)
+    ));
+
+    assert_eq!(
+        mock.wait_for_messages(1).await,
+        vec![json!({ "msg": "DataUpdate", "body": [] })]
+    );
+}
+
+#[tokio::test]
+async fn test_delivering_unknown_messages() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    mock.push_message(json!({ "msg": "SomethingNew", "body": [{ "value": 1 }] }));
+
+    let received_events = Arc::new(Mutex::new(Vec::new()));
+
+    {
+        let received_events = Arc::clone(&received_events);
+
+        let outcome = timeout(
+            Duration::from_secs(5),
+            listener.listen::<_, _, ()>(|event, _, _| {
+                let received_events = Arc::clone(&received_events);
+
+                async move {
+                    let mut received_events = received_events.lock().await;
+
+                    let ListenerEvent::Event(event) = event else {
+                        panic!("Expected only events from Ring, got {event:?}");
+                    };
+
+                    received_events.push(event);
+
+                    Ok(received_events.len() < 3)
+                }
+            }),
+        )
+        .await;
+
+        assert!(
+            matches!(outcome, Ok(Ok(()))),
+            "Outcome of event listening should be Ok"
+        );
+    }
+
+    assert_eq!(
+        received_events.lock().await[2].message,
+        Message::Unknown {
+            msg: "SomethingNew".to_string(),
+            body: json!({ "body": [{ "value": 1 }] }),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_subscribing_to_filtered_events() {
+    let mock = start_mock_at_home().await;
+    mock.add_alarm_device(fixtures::alarm_device(
+        "sensor.contact",
+        "mock-zid-1",
+        "Front Door",
+    ));
+    mock.add_alarm_device(fixtures::alarm_device(
+        "sensor.contact",
+        "mock-zid-2",
+        "Back Door",
+    ));
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let mut front_door_opened =
+        listener.subscribe(Filter::device(Zid::new("mock-zid-1")).kind(EventKind::Faulted));
+    let mut back_door = listener.subscribe(Filter::device(Zid::new("mock-zid-2")));
+
+    mock.set_alarm_device_faulted("mock-zid-2", true);
+    mock.set_alarm_device_faulted("mock-zid-1", true);
+    mock.set_alarm_device_faulted("mock-zid-1", false);
+
+    // Events are routed without the listener itself being read from.
+    let event = timeout(Duration::from_secs(5), front_door_opened.recv())
+        .await
+        .expect("Expected the front door to be opened")
+        .expect("The subscription should not end");
+
+    let Message::DataUpdate(update) = event.message else {
+        panic!("Expected a data update");
+    };
+
+    assert_eq!(update["body"][0]["general"]["v2"]["zid"], "mock-zid-1");
+    assert_eq!(update["body"][0]["device"]["v1"]["faulted"], true);
+
+    timeout(Duration::from_secs(5), back_door.recv())
+        .await
+        .expect("Expected the back door to be opened")
+        .expect("The subscription should not end");
+
+    // By the time the back door update has been routed, so have the others.
+    assert!(front_door_opened.try_recv().is_err());
+    assert!(back_door.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_ending_subscriptions_when_the_connection_closes() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let mut events = listener.subscribe(Filter::any());
+
+    mock.disconnect_websockets();
+
+    let ended = timeout(Duration::from_secs(5), async {
+        while events.recv().await.is_some() {}
+    })
+    .await;
+
+    assert!(
+        ended.is_ok(),
+        "Expected the subscription to end with the connection"
+    );
+}
+
+#[tokio::test]
+async fn test_reconnecting_listener_after_connection_is_lost() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events")
+        .reconnecting(ReconnectPolicy::default().initial_delay(Duration::from_millis(10)));
+
+    let subscription = Event::new(Message::DataUpdate(json!({ "body": [] })));
+
+    listener
+        .add_subscription(subscription)
+        .await
+        .expect("Subscribing should not fail");
+
+    let received_events = Arc::new(Mutex::new(Vec::new()));
+
+    {
+        let received_events = Arc::clone(&received_events);
+        let mock = &mock;
+
+        let outcome = timeout(
+            Duration::from_secs(5),
+            listener.listen::<_, _, ()>(|event, _, _| {
+                let received_events = Arc::clone(&received_events);
+
+                async move {
+                    let mut received_events = received_events.lock().await;
+
+                    // Drop the connection once the initial greeting and subscription have
+                    // been exchanged.
+                    if matches!(
+                        event,
+                        ListenerEvent::Event(Event {
+                            message: Message::SubscriptionTopicsInfo(_)
+                        })
+                    ) && received_events.len() == 1
+                    {
+                        mock.wait_for_messages(1).await;
+                        mock.disconnect_websockets();
+                    }
+
+                    let reconnected = event == ListenerEvent::Reconnected;
+
+                    received_events.push(event);
+
+                    Ok(!reconnected)
+                }
+            }),
+        )
+        .await;
+
+        assert!(
+            matches!(outcome, Ok(Ok(()))),
+            "Outcome of event listening should be Ok"
+        );
+    }
+
+    let events = received_events.lock().await;
+
+    assert!(matches!(
+        events[0],
+        ListenerEvent::Event(Event {
+            message: Message::SessionInfo(_)
+        })
+    ));
+    assert!(matches!(
+        events[1],
+        ListenerEvent::Event(Event {
+            message: Message::SubscriptionTopicsInfo(_)
+        })
+    ));
+    assert_eq!(
+        events[2..],
+        [ListenerEvent::Disconnected, ListenerEvent::Reconnected]
+    );
+
+    assert_eq!(
+        mock.request_count("/api/v1/clap/tickets"),
+        2,
+        "A fresh ticket should have been generated to reconnect"
+    );
+    assert_eq!(
+        mock.wait_for_messages(2).await,
+        vec![json!({ "msg": "DataUpdate", "body": [] }); 2],
+        "The subscription should have been sent again after reconnecting"
+    );
+}
+
+#[tokio::test]
+async fn test_streaming_events_in_location() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let sender = listener.sender();
+
+    let mut events = listener.into_stream().boxed();
+
+    let initial_events = timeout(
+        Duration::from_secs(5),
+        events.by_ref().take(2).collect::<Vec<_>>(),
+    )
+    .await
+    .expect("Expected the initial events to be received");
+
+    assert!(matches!(
+        initial_events[0],
+        Ok(ListenerEvent::Event(Event {
+            message: Message::SessionInfo(_)
+        }))
+    ));
+
+    tokio::spawn(async move {
+        sender
+            .send(Event::new(Message::DataUpdate(json!({ "body": [] }))))
+            .await
+            .expect("Sending an event should not fail");
+    })
+    .await
+    .expect("Sending task should not panic");
+
+    assert_eq!(
+        mock.wait_for_messages(1).await,
+        vec![json!({ "msg": "DataUpdate", "body": [] })]
+    );
+
+    mock.push_message(json!({ "msg": "DataUpdate", "datatype": "DeviceInfoDocType", "body": [] }));
+
+    let pushed = timeout(Duration::from_secs(5), events.next())
+        .await
+        .expect("Expected the pushed event to be received");
+
+    assert!(matches!(
+        pushed,
+        Some(Ok(ListenerEvent::Event(Event {
+            message: Message::DataUpdate(_)
+        })))
+    ));
+}
+
+#[tokio::test]
+async fn test_reconnecting_once_per_lost_connection() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events")
+        .reconnecting(ReconnectPolicy::default().initial_delay(Duration::from_millis(10)));
+
+    let mut events = Vec::new();
+
+    timeout(Duration::from_secs(5), async {
+        for _ in 0..2 {
+            listener.next().await;
+        }
+
+        mock.disconnect_websockets();
+
+        // Let the old WebSocket report both its error and its end before reconnecting.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        for _ in 0..4 {
+            let event = listener.next().await.expect("The listener should not stop");
+
+            events.push(event.expect("Events should be decoded"));
+        }
+    })
+    .await
+    .expect("Expected the listener to reconnect");
+
+    assert_eq!(
+        events[..2],
+        [ListenerEvent::Disconnected, ListenerEvent::Reconnected]
+    );
+    assert!(
+        matches!(
+            events[2],
+            ListenerEvent::Event(Event {
+                message: Message::SessionInfo(_)
+            })
+        ) && matches!(
+            events[3],
+            ListenerEvent::Event(Event {
+                message: Message::SubscriptionTopicsInfo(_)
+            })
+        ),
+        "Expected the greeting from the new connection, got {events:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_listening_from_a_spawned_task() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let listener = {
+        // The client can be dropped, as the location owns a handle to the same session.
+        let client = client.clone();
+
+        let location = only_location(&client).await;
+
+        tokio::spawn(async move {
+            let mut listener = location
+                .get_listener()
+                .await
+                .expect("Should be able to listen for events");
+
+            listener.next().await
+        })
+    };
+
+    let event = timeout(Duration::from_secs(5), listener)
+        .await
+        .expect("Expected an event to be received")
+        .expect("Listener task should not panic");
+
+    assert!(matches!(
+        event,
+        Some(Ok(ListenerEvent::Event(Event {
+            message: Message::SessionInfo(_)
+        })))
+    ));
+}
+
+/// Wait for the next data update delivered by an event hub, skipping any other events (such
+/// as the greeting sent when each location connects).
+async fn receive_update(receiver: &mut UnboundedReceiver<LocationEvent>) -> LocationEvent {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let event = receiver.recv().await.expect("The hub should not stop");
+
+            if matches!(
+                event.event,
+                ListenerEvent::Event(Event {
+                    message: Message::DataUpdate(_)
+                })
+            ) {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("Expected an update to be received")
+}
+
+#[tokio::test]
+async fn test_fanning_out_events_from_many_locations() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    let hub = client
+        .event_hub()
+        .connect()
+        .await
+        .expect("Should be able to connect to every location");
+
+    let mut location_ids = hub.location_ids();
+    location_ids.sort_unstable();
+
+    assert_eq!(location_ids, vec!["mock-location-1", "mock-location-2"]);
+
+    let mut everything = hub.subscribe();
+    let mut office = hub.subscribe_to_location("mock-location-2");
+    let mut sensor = hub.subscribe_to_device(&Zid::new("mock-zid-1"));
+    let mut other_sensor = hub.subscribe_to_device(&Zid::new("mock-zid-2"));
+
+    // Every connected WebSocket receives the update, so it is delivered once per location.
+    mock.push_message(json!({
+        "msg": "DataUpdate",
+        "datatype": "DeviceInfoDocType",
+        "body": [{ "general": { "v2": { "zid": "mock-zid-1" } } }]
+    }));
+
+    let mut received = [
+        receive_update(&mut everything).await.location_id,
+        receive_update(&mut everything).await.location_id,
+    ];
+    received.sort_unstable();
+
+    assert_eq!(received, ["mock-location-1", "mock-location-2"]);
+
+    assert_eq!(
+        receive_update(&mut office).await.location_id,
+        "mock-location-2"
+    );
+    assert!(office.try_recv().is_err());
+
+    receive_update(&mut sensor).await;
+    receive_update(&mut sensor).await;
+    assert!(other_sensor.try_recv().is_err());
+
+    hub.sender("mock-location-1")
+        .expect("Location should be listened to")
+        .send(Event::new(Message::DataUpdate(json!({ "body": [] }))))
+        .await
+        .expect("Sending an event should not fail");
+
+    assert_eq!(
+        mock.wait_for_messages(1).await,
+        vec![json!({ "msg": "DataUpdate", "body": [] })]
+    );
+}
+
+#[tokio::test]
+async fn test_subscribing_before_the_hub_connects() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    let builder = client.event_hub();
+    let mut office = builder.subscribe_to_location("mock-location-2");
+
+    let _hub = builder
+        .connect()
+        .await
+        .expect("Should be able to connect to every location");
+
+    // The greeting is sent as soon as the location connects, before the hub is returned.
+    let greeting = timeout(Duration::from_secs(5), office.recv())
+        .await
+        .expect("Expected the greeting to be received")
+        .expect("The hub should not stop");
+
+    assert_eq!(greeting.location_id, "mock-location-2");
+    assert!(matches!(
+        greeting.event,
+        ListenerEvent::Event(Event {
+            message: Message::SessionInfo(_)
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_forgetting_locations_which_stop_listening() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    let hub = client
+        .event_hub()
+        .reconnect_policy(
+            ReconnectPolicy::default()
+                .initial_delay(Duration::from_millis(10))
+                .max_attempts(1),
+        )
+        .connect()
+        .await
+        .expect("Should be able to connect to every location");
+
+    // Only one of the locations is able to reconnect.
+    mock.enqueue_response(
+        "/api/v1/clap/tickets",
+        503,
+        json!({ "error": "Unavailable" }),
+    );
+    mock.disconnect_websockets();
+
+    timeout(Duration::from_secs(5), async {
+        while hub.location_ids().len() > 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Expected the location which stopped to be forgotten");
+
+    let remaining = hub.location_ids();
+
+    let stopped = if remaining[0] == "mock-location-1" {
+        "mock-location-2"
+    } else {
+        "mock-location-1"
+    };
+
+    assert!(hub.sender(&remaining[0]).is_some());
+    assert!(hub.sender(stopped).is_none());
+}
+
+#[tokio::test]
+async fn test_listening_to_selected_locations() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    let hub = client
+        .event_hub()
+        .locations(["mock-location-2"])
+        .connect()
+        .await
+        .expect("Should be able to connect to the location");
+
+    assert_eq!(hub.location_ids(), vec!["mock-location-2"]);
+    assert!(hub.sender("mock-location-1").is_none());
+}
+
+#[tokio::test]
+async fn test_skipping_locations_which_cannot_be_connected_to() {
+    let mock = MockRing::start().await;
+
+    mock.add_location(fixtures::location("mock-location-1", "Home"));
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    mock.enqueue_response(
+        "/api/v1/clap/tickets",
+        503,
+        json!({ "error": "Unavailable" }),
+    );
+
+    let hub = client
+        .event_hub()
+        .connect()
+        .await
+        .expect("Should be able to connect to the reachable location");
+
+    assert_eq!(hub.location_ids().len(), 1);
+
+    for _ in 0..2 {
+        mock.enqueue_response(
+            "/api/v1/clap/tickets",
+            503,
+            json!({ "error": "Unavailable" }),
+        );
+    }
+
+    let unreachable = client.event_hub().connect().await;
+
+    assert!(
+        matches!(unreachable, Err(ApiError::ServerError { .. })),
+        "Expected connecting to fail when no location is reachable, got {unreachable:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_listing_alarm_devices() {
+    let mock = start_mock_at_home().await;
+    mock.add_alarm_device(fixtures::alarm_device(
+        "sensor.contact",
+        "mock-zid-1",
+        "Front Door",
+    ));
+    mock.add_alarm_device(fixtures::alarm_device(
+        "security-panel",
+        "mock-zid-2",
+        "Alarm",
+    ));
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let devices = timeout(Duration::from_secs(5), listener.get_alarm_devices())
+        .await
+        .expect("Expected the device list to be received")
+        .expect("Listing alarm devices should not fail");
+
+    assert_eq!(devices.len(), 2);
+    assert!(matches!(devices[0], AlarmDevice::ContactSensor(_)));
+    assert_eq!(devices[0].zid(), &Zid::new("mock-zid-1"));
+    assert!(matches!(devices[1], AlarmDevice::SecurityPanel(_)));
+
+    assert_eq!(
+        mock.wait_for_messages(1).await,
+        vec![
+            json!({ "msg": "DeviceInfoDocGetList", "dst": "mock-asset-mock-location-id", "seq": 1 })
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_requests_receive_their_own_response() {
+    let mock = start_mock_at_home().await;
+    mock.add_alarm_device(fixtures::alarm_device(
+        "security-panel",
+        "mock-zid-panel",
+        "Alarm",
+    ));
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    mock.push_message(json!({ "msg": "DataUpdate", "datatype": "HubDisconnectionEventType" }));
+
+    let response = timeout(
+        Duration::from_secs(5),
+        listener.request(Event::new(Message::DeviceInfoDocGetList(json!({
+            "dst": "mock-asset-mock-location-id"
+        })))),
+    )
+    .await
+    .expect("Expected a response to be received")
+    .expect("Requesting the device list should not fail");
+
+    let Message::DeviceInfoDocGetList(body) = response.message else {
+        panic!("Expected the device list in response to the request");
+    };
+
+    assert_eq!(body["seq"], 1);
+    assert_eq!(body["body"][0]["general"]["v2"]["zid"], "mock-zid-panel");
+
+    // The unrelated event is still delivered by the listener.
+    let events = timeout(Duration::from_secs(5), async {
+        let mut events = Vec::new();
+
+        while events.len() < 3 {
+            events.push(
+                listener
+                    .next()
+                    .await
+                    .expect("The listener should not stop")
+                    .expect("Reading an event should not fail"),
+            );
+        }
+
+        events
+    })
+    .await
+    .expect("Expected the events to be received");
+
+    assert!(matches!(
+        events[0],
+        ListenerEvent::Event(Event {
+            message: Message::SessionInfo(_)
+        })
+    ));
+    assert!(matches!(
+        events[1],
+        ListenerEvent::Event(Event {
+            message: Message::SubscriptionTopicsInfo(_)
+        })
+    ));
+    assert!(matches!(
+        events[2],
+        ListenerEvent::Event(Event {
+            message: Message::DataUpdate(_)
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_responses_have_the_same_name_as_their_request() {
+    let mock = start_mock_at_home().await;
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let sender = listener.sender();
+
+    // The mock does not respond to room lists, so the response is pushed instead.
+    let request = tokio::spawn(async move {
+        sender
+            .request(Event::new(Message::RoomGetList(json!({
+                "dst": "mock-asset-mock-location-id"
+            }))))
+            .await
+    });
+
+    mock.wait_for_messages(1).await;
+
+    // An unrelated message which happens to have the same sequence number as the request.
+    mock.push_message(json!({
+        "msg": "DataUpdate",
+        "datatype": "HubDisconnectionEventType",
+        "seq": 1
+    }));
+    mock.push_message(json!({
+        "msg": "RoomGetList",
+        "datatype": "RoomListV2Type",
+        "seq": 1,
+        "body": []
+    }));
+
+    let response = timeout(Duration::from_secs(5), request)
+        .await
+        .expect("Expected a response to be received")
+        .expect("The request should not panic")
+        .expect("Requesting the room list should not fail");
+
+    assert!(matches!(response.message, Message::RoomGetList(_)));
+
+    // The unrelated message is delivered as an event instead.
+    let update = timeout(Duration::from_secs(5), async {
+        loop {
+            let event = listener
+                .next()
+                .await
+                .expect("The listener should not stop")
+                .expect("Reading an event should not fail");
+
+            if let ListenerEvent::Event(Event {
+                message: message @ Message::DataUpdate(_),
+            }) = event
+            {
+                return message;
+            }
+        }
+    })
+    .await
+    .expect("Expected the update to be received");
+
+    assert_eq!(update.seq(), Some(1));
+}
+
+#[tokio::test]
+async fn test_requests_time_out_without_a_response() {
+    let mock = start_mock_at_home().await;
+
+    let client = mock
+        .client_builder()
+        .request_timeout(Duration::from_millis(100))
+        .build();
+
+    log_in(&mock, &client).await;
+
+    let location = only_location(&client).await;
+
+    let listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    // The mock does not respond to data updates.
+    let outcome = listener
+        .request(Event::new(Message::DataUpdate(json!({ "body": [] }))))
+        .await;
+
+    assert!(matches!(outcome, Err(ApiError::RequestTimedOut)));
+}
+
+#[tokio::test]
+async fn test_arming_and_disarming_alarm() {
+    let mock = start_mock_at_home().await;
+    mock.add_alarm_device(fixtures::alarm_device(
+        "security-panel",
+        "mock-zid-panel",
+        "Alarm",
+    ));
+    mock.add_alarm_device(fixtures::alarm_device(
+        "sensor.contact",
+        "mock-zid-door",
+        "Front Door",
+    ));
+    mock.set_alarm_device_faulted("mock-zid-door", true);
+
+    let client = logged_in_client(&mock).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let refused = timeout(
+        Duration::from_secs(5),
+        listener.set_alarm_mode(AlarmMode::Away, &[]),
+    )
+    .await
+    .expect("Expected a response to arming the alarm");
+
+    assert!(
+        matches!(
+            &refused,
+            Err(ApiError::AlarmModeRefused { faulted_sensors, .. })
+                if faulted_sensors == &[Zid::new("mock-zid-door")]
+        ),
+        "Expected arming to be refused, got {refused:?}"
+    );
+    assert_eq!(mock.alarm_mode().as_deref(), Some("none"));
+
+    timeout(
+        Duration::from_secs(5),
+        listener.set_alarm_mode(AlarmMode::Away, &[Zid::new("mock-zid-door")]),
+    )
+    .await
+    .expect("Expected a response to arming the alarm")
+    .expect("Arming with the faulted sensor bypassed should not fail");
+
+    assert_eq!(mock.alarm_mode().as_deref(), Some("all"));
+
+    // The confirmation is still delivered through the listener, like any other event.
+    let confirmation = timeout(Duration::from_secs(5), async {
+        loop {
+            let event = listener.next().await.expect("The listener should not stop");
+
+            if let Ok(ListenerEvent::Event(Event {
+                message: Message::DataUpdate(message),
+            })) = event
+            {
+                return message;
+            }
+        }
+    })
+    .await
+    .expect("Expected the mode change to be delivered");
+
+    assert_eq!(confirmation["body"][0]["device"]["v1"]["mode"], "all");
+
+    timeout(
+        Duration::from_secs(5),
+        listener.set_alarm_mode(AlarmMode::Disarmed, &[]),
+    )
+    .await
+    .expect("Expected a response to disarming the alarm")
+    .expect("Disarming should not fail");
+
+    assert_eq!(mock.alarm_mode().as_deref(), Some("none"));
+}
+
+#[tokio::test]
+async fn test_reporting_connections_lost_while_arming() {
+    let mock = start_mock_at_home().await;
+
+    mock.add_alarm_device(fixtures::alarm_device(
+        "security-panel",
+        "mock-zid-panel",
+        "Alarm",
+    ));
+
+    let client = mock
+        .client_builder()
+        .request_timeout(Duration::from_secs(30))
+        .build();
+
+    log_in(&mock, &client).await;
+
+    let location = only_location(&client).await;
+
+    let mut listener = location
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events")
+        .reconnecting(ReconnectPolicy::default());
+
+    mock.disconnect_before_confirming_alarm_mode();
+
+    // The lost connection is reported straight away, rather than after the request timeout.
+    let armed = timeout(
+        Duration::from_secs(5),
+        listener.set_alarm_mode(AlarmMode::Away, &[]),
+    )
+    .await
+    .expect("Expected the lost connection to be reported");
+
+    assert!(
+        matches!(armed, Err(ApiError::ConnectionClosed)),
+        "Expected the connection to be closed, got {armed:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_refusing_connections_to_other_locations() {
+    let mock = start_mock_at_home().await;
+
+    mock.add_location(fixtures::location("mock-location-2", "Office"));
+
+    let client = logged_in_client(&mock).await;
+
+    let locations = client
+        .get_locations()
+        .await
+        .expect("Expected to get locations");
+
+    let home = &locations[0];
+
+    let mut listener = locations[1]
+        .get_listener()
+        .await
+        .expect("Should be able to listen for events");
+
+    let outcome = listener
+        .listen(|_, _, connection| async move {
+            let connection = connection.lock().await;
+
+            home.set_alarm_mode(&connection, AlarmMode::Away, &[])
+                .await?;
+
+            Ok(false)
+        })
+        .await;
+
+    assert!(
+        matches!(
+            &outcome,
+            Err(ApiError::WrongLocation { location, connection })
+                if location == "mock-location-id" && connection == "mock-location-2"
+        ),
+        "Expected the connection to be refused, got {outcome:?}"
+    );
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use dotenvy_macro::dotenv;
-use ring_client::location::Message;
+use ring_client::location::{ListenerEvent, Message};
 use ring_client::{Client, OperatingSystem, authentication::Credentials};
 use tokio::{sync::Mutex, time::timeout};
 
@@ -62,6 +62,10 @@ async fn test_listening_for_events_in_location() {
 
                 let mut received_events = received_events.lock().await;
 
+                let ListenerEvent::Event(event) = event else {
+                    return Err("Received unexpected connection change");
+                };
+
                 if !matches!(
                     event.message,
                     Message::SessionInfo(_) | Message::SubscriptionTopicsInfo(_)